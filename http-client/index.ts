@@ -11,6 +11,7 @@ import type {
 import type {
   Author,
   Config,
+  Page,
   SeedingPolicy,
   DefaultSeedingPolicy,
 } from "./lib/shared.js";
@@ -73,6 +74,7 @@ export type {
   IssueState,
   LifecycleState,
   Merge,
+  Page,
   Patch,
   PatchState,
   Reaction,
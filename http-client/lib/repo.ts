@@ -3,6 +3,7 @@ import type { Fetcher, RequestOptions } from "./fetcher.js";
 import type { Commit, Commits } from "./repo/commit.js";
 import type { Issue } from "./repo/issue.js";
 import type { Patch } from "./repo/patch.js";
+import type { Page } from "./shared.js";
 
 import {
   array,
@@ -26,7 +27,7 @@ import {
 } from "./repo/commit.js";
 import { issueSchema, issuesSchema } from "./repo/issue.js";
 import { patchSchema, patchesSchema } from "./repo/patch.js";
-import { authorSchema } from "./shared.js";
+import { authorSchema, pageSchema } from "./shared.js";
 
 const repoSchema = object({
   rid: string(),
@@ -60,7 +61,7 @@ const repoSchema = object({
   ]),
   seeding: number(),
 });
-const reposSchema = array(repoSchema);
+const reposSchema = pageSchema(repoSchema);
 
 export type Repo = z.infer<typeof repoSchema>;
 
@@ -141,7 +142,7 @@ export class Client {
     delegateId: string,
     query?: RepoListQuery,
     options?: RequestOptions,
-  ): Promise<Repo[]> {
+  ): Promise<Page<Repo>> {
     return this.#fetcher.fetchOk(
       {
         method: "GET",
@@ -167,7 +168,7 @@ export class Client {
   public async getAll(
     query?: RepoListQuery,
     options?: RequestOptions,
-  ): Promise<Repo[]> {
+  ): Promise<Page<Repo>> {
     return this.#fetcher.fetchOk(
       {
         method: "GET",
@@ -363,7 +364,7 @@ export class Client {
       status?: string;
     },
     options?: RequestOptions,
-  ): Promise<Issue[]> {
+  ): Promise<Page<Issue>> {
     return this.#fetcher.fetchOk(
       {
         method: "GET",
@@ -398,7 +399,7 @@ export class Client {
       status?: string;
     },
     options?: RequestOptions,
-  ): Promise<Patch[]> {
+  ): Promise<Page<Patch>> {
     return this.#fetcher.fetchOk(
       {
         method: "GET",
@@ -23,6 +23,7 @@ import {
   string,
   union,
 } from "zod";
+import { pageSchema } from "../shared.js";
 export {
   commitBlobSchema,
   commitHeaderSchema,
@@ -238,4 +239,4 @@ const commitSchema = object({
 
 type Commits = z.infer<typeof commitsSchema>;
 
-const commitsSchema = array(commitHeaderSchema);
+const commitsSchema = pageSchema(commitHeaderSchema);
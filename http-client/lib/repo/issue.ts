@@ -2,7 +2,8 @@ import type { ZodSchema } from "zod";
 import * as z from "zod";
 
 import { commentSchema } from "./comment.js";
-import { authorSchema } from "../shared.js";
+import type { Page } from "../shared.js";
+import { authorSchema, pageSchema } from "../shared.js";
 
 export type IssueState =
   | { status: "open" }
@@ -28,4 +29,6 @@ export const issueSchema = z.object({
 
 export type Issue = z.infer<typeof issueSchema>;
 
-export const issuesSchema = z.array(issueSchema) satisfies ZodSchema<Issue[]>;
+export const issuesSchema = pageSchema(issueSchema) satisfies ZodSchema<
+  Page<Issue>
+>;
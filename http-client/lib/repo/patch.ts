@@ -12,7 +12,8 @@ import {
   tuple,
   union,
 } from "zod";
-import { authorSchema } from "../shared.js";
+import type { Page } from "../shared.js";
+import { authorSchema, pageSchema } from "../shared.js";
 
 export type PatchState = z.infer<typeof patchStateSchema>;
 
@@ -98,7 +99,9 @@ export const patchSchema = object({
 
 export type Patch = z.infer<typeof patchSchema>;
 
-export const patchesSchema = array(patchSchema) satisfies ZodSchema<Patch[]>;
+export const patchesSchema = pageSchema(patchSchema) satisfies ZodSchema<
+  Page<Patch>
+>;
 
 export type LifecycleState =
   | { status: "draft" }
@@ -14,6 +14,20 @@ export const seedingPolicySchema = z.union([
 
 export type SeedingPolicy = z.infer<typeof seedingPolicySchema>;
 
+export type Page<T> = {
+  items: T[];
+  total?: number;
+  nextCursor: string | null;
+};
+
+export function pageSchema<T extends z.ZodTypeAny>(itemSchema: T) {
+  return z.object({
+    items: z.array(itemSchema),
+    total: z.number().optional(),
+    nextCursor: z.string().nullable(),
+  });
+}
+
 const defaultSeedingPolicySchema = z.union([
   z.object({
     default: z.literal("block"),
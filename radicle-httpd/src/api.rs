@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::fs;
 use std::sync::Arc;
 
 use axum::response::{IntoResponse, Json};
@@ -9,37 +10,92 @@ use serde_json::{json, Value};
 use radicle::identity::doc::PayloadId;
 use radicle::identity::{DocAt, RepoId};
 use radicle::issue::cache::Issues as _;
+use radicle::node::device::BoxedDevice;
 use radicle::node::routing::Store;
-use radicle::node::NodeId;
+use radicle::node::{Handle as _, NodeId};
 use radicle::patch::cache::Patches as _;
 use radicle::storage::git::Repository;
 use radicle::storage::{ReadRepository, ReadStorage};
-use radicle::Profile;
+use radicle::{Node, Profile};
 
+pub(crate) mod auth;
 mod error;
+mod etag;
+mod events;
+mod interdiff;
 mod json;
+mod pagination;
 pub(crate) mod query;
+mod search;
+mod signature;
 mod v1;
 
 use crate::api::error::Error;
-use crate::cache::Cache;
+use crate::cache::{self, Cache};
 use crate::Options;
 
 pub const RADICLE_VERSION: &str = env!("RADICLE_VERSION");
 // This version has to be updated on every breaking change to the radicle-httpd API.
-pub const API_VERSION: &str = "6.1.0";
+pub const API_VERSION: &str = "7.0.0";
+
+/// File names looked up when resolving a repository's README.
+pub const README_PATHS: [&str; 7] = [
+    "README",
+    "README.md",
+    "README.markdown",
+    "README.txt",
+    "README.rst",
+    "README.org",
+    "Readme.md",
+];
 
 #[derive(Clone)]
 pub struct Context {
     profile: Arc<Profile>,
     cache: Option<Cache>,
+    search: search::Search,
+    events: events::Events,
+    sessions: auth::Sessions,
 }
 
 impl Context {
-    pub fn new(profile: Arc<Profile>, options: &Options) -> Self {
-        Self {
+    #[allow(clippy::result_large_err)]
+    pub fn new(profile: Arc<Profile>, options: &Options) -> Result<Self, Error> {
+        let dir = profile.home.path().join("httpd");
+        fs::create_dir_all(&dir)?;
+        let sessions = auth::Sessions::open(dir.join("sessions.db"))?;
+        let endpoints = options.cache_endpoints.iter().copied();
+        let cache = match &options.cache_dir {
+            Some(path) => Some(Cache::with_store(
+                cache::Disk::open(path, options.cache_dir_size)?,
+                endpoints,
+            )),
+            None => options
+                .cache
+                .map(|size| Cache::with_store(cache::Memory::new(size), endpoints)),
+        };
+
+        Ok(Self {
             profile,
-            cache: options.cache.map(Cache::new),
+            cache,
+            search: search::Search::default(),
+            events: events::Events::default(),
+            sessions,
+        })
+    }
+
+    /// Get the cached response of an immutable endpoint, or compute it and cache it.
+    #[allow(clippy::result_large_err)]
+    pub fn cached(
+        &self,
+        endpoint: cache::Endpoint,
+        rid: RepoId,
+        id: impl ToString,
+        f: impl FnOnce() -> Result<Value, Error>,
+    ) -> Result<Value, Error> {
+        match &self.cache {
+            Some(cache) => cache.get_or_insert(endpoint, rid, id, f),
+            None => f(),
         }
     }
 
@@ -99,19 +155,49 @@ impl Context {
         })
     }
 
-    /// Get a repository by RID, checking to make sure we're allowed to view it.
+    /// Get a repository by RID, checking to make sure the session is allowed to view it.
     #[allow(clippy::result_large_err)]
-    pub fn repo(&self, rid: RepoId) -> Result<(Repository, DocAt), error::Error> {
+    pub fn repo(
+        &self,
+        rid: RepoId,
+        session: Option<&auth::Session>,
+    ) -> Result<(Repository, DocAt), error::Error> {
         let repo = self.profile.storage.repository(rid)?;
         let doc = repo.identity_doc()?;
-        // Don't allow accessing private repos.
-        if doc.visibility().is_private() {
+        // Private repos are only accessible to the sessions they are visible to.
+        if !auth::can_view(&doc, session) {
             return Err(Error::NotFound);
         }
         Ok((repo, doc))
     }
 
-    #[cfg(test)]
+    /// Get a signer to make changes on behalf of an authenticated session.
+    ///
+    /// Changes can only be signed with the node's own key, so the session must have been
+    /// authenticated with that key.
+    #[allow(clippy::result_large_err)]
+    pub fn signer(&self, session: &auth::Session) -> Result<BoxedDevice, error::Error> {
+        if session.public_key != self.profile.public_key {
+            return Err(Error::Forbidden);
+        }
+        Ok(self.profile.signer()?)
+    }
+
+    /// Announce the refs of a repository to the network, if the node is running.
+    pub fn announce_refs(&self, rid: RepoId) {
+        let mut node = Node::new(self.profile.socket());
+        if !node.is_running() {
+            return;
+        }
+        if let Err(err) = node.announce_refs(rid) {
+            tracing::error!("Error announcing refs of {rid}: {err}");
+        }
+    }
+
+    pub fn sessions(&self) -> &auth::Sessions {
+        &self.sessions
+    }
+
     pub fn profile(&self) -> &Arc<Profile> {
         &self.profile
     }
@@ -138,102 +224,6 @@ async fn root_handler() -> impl IntoResponse {
     Json(response)
 }
 
-mod search {
-    use std::cmp::Ordering;
-    use std::collections::BTreeMap;
-
-    use serde::{Deserialize, Serialize};
-    use serde_json::json;
-
-    use radicle::identity::doc::{Payload, PayloadId};
-    use radicle::identity::RepoId;
-    use radicle::node::routing::Store;
-    use radicle::node::{AliasStore, Database};
-    use radicle::profile::Aliases;
-    use radicle::storage::RepositoryInfo;
-
-    #[derive(Serialize, Deserialize)]
-    #[serde(rename_all = "camelCase")]
-    pub struct SearchQueryString {
-        pub q: Option<String>,
-        pub page: Option<usize>,
-        pub per_page: Option<usize>,
-    }
-
-    #[derive(Serialize, Deserialize, Eq, Debug)]
-    pub struct SearchResult {
-        pub rid: RepoId,
-        pub payloads: BTreeMap<PayloadId, Payload>,
-        pub delegates: Vec<serde_json::Value>,
-        pub seeds: usize,
-        #[serde(skip)]
-        pub index: usize,
-    }
-
-    impl SearchResult {
-        pub fn new(
-            q: &str,
-            info: RepositoryInfo,
-            db: &Database,
-            aliases: &Aliases,
-        ) -> Option<Self> {
-            if info.doc.visibility().is_private() {
-                return None;
-            }
-            let Ok(Some(index)) = info.doc.project().map(|p| p.name().find(q)) else {
-                return None;
-            };
-            let seeds = db.count(&info.rid).unwrap_or_default();
-            let delegates = info
-                .doc
-                .delegates()
-                .iter()
-                .map(|did| match aliases.alias(did) {
-                    Some(alias) => json!({
-                        "id": did,
-                        "alias": alias,
-                    }),
-                    None => json!({
-                        "id": did,
-                    }),
-                })
-                .collect::<Vec<_>>();
-
-            Some(SearchResult {
-                rid: info.rid,
-                payloads: info.doc.payload().clone(),
-                delegates,
-                seeds,
-                index,
-            })
-        }
-    }
-
-    impl Ord for SearchResult {
-        fn cmp(&self, other: &Self) -> Ordering {
-            match (self.index, other.index) {
-                (0, 0) => self.seeds.cmp(&other.seeds),
-                (0, _) => std::cmp::Ordering::Less,
-                (_, 0) => std::cmp::Ordering::Greater,
-                (ai, bi) if ai == bi => self.seeds.cmp(&other.seeds),
-                (_, _) => self.seeds.cmp(&other.seeds),
-            }
-        }
-    }
-
-    impl PartialOrd for SearchResult {
-        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-            Some(self.cmp(other))
-        }
-    }
-
-    impl PartialEq for SearchResult {
-        fn eq(&self, other: &Self) -> bool {
-            self.rid == other.rid
-        }
-    }
-}
-
 mod repo {
     use std::collections::BTreeMap;
 
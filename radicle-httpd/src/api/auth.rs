@@ -0,0 +1,467 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::header::{AUTHORIZATION, DATE};
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, Method, Uri};
+use base64::prelude::{BASE64_STANDARD, BASE64_URL_SAFE_NO_PAD};
+use base64::Engine;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlite as sql;
+
+use radicle::crypto::{PublicKey, Signature};
+use radicle::identity::{Did, Doc};
+
+use crate::api::error::Error;
+use crate::api::Context;
+
+/// How long a client has to answer a session challenge.
+pub const CHALLENGE_TTL: Duration = Duration::from_secs(60 * 5);
+/// How long an authorized session is valid for.
+pub const SESSION_TTL: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+/// How far the date of a request signed with an HTTP signature may be from the current time.
+pub const SIGNATURE_TTL: Duration = Duration::from_secs(60 * 5);
+/// Number of random bytes in session ids, nonces and bearer tokens.
+const RANDOM_BYTES: usize = 24;
+/// How long to wait for the database to be unlocked.
+const DB_TIMEOUT: Duration = Duration::from_secs(6);
+/// Max number of pending challenges. Challenges can be created without authentication, so
+/// this bounds the size of the database until they expire.
+const MAX_CHALLENGES: i64 = 1024;
+
+/// An authenticated session.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    /// Session identifier. Unlike the bearer token, this isn't secret.
+    pub id: String,
+    /// The key the session was authenticated with.
+    pub public_key: PublicKey,
+    /// When the session was issued, in seconds since the epoch.
+    pub issued_at: i64,
+    /// When the session expires, in seconds since the epoch.
+    pub expires_at: i64,
+}
+
+/// Error verifying the HTTP signature of a request.
+#[derive(Debug, thiserror::Error)]
+pub enum SignatureError {
+    /// The `Signature` header can't be parsed.
+    #[error("malformed signature header")]
+    Malformed,
+    /// A header covered by the signature is missing from the request.
+    #[error("signed header '{0}' is missing")]
+    MissingHeader(String),
+    /// The signature doesn't cover the `Date` header, or the date is too far from now.
+    #[error("signature date is missing or out of range")]
+    Date,
+    /// The signature doesn't cover a header it's required to, so it could be replayed
+    /// against another request.
+    #[error("signature doesn't cover '{0}'")]
+    Uncovered(&'static str),
+    /// The signature doesn't match the request.
+    #[error("invalid signature")]
+    Invalid,
+}
+
+/// A pending session, waiting for its nonce to be signed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Challenge {
+    /// Session identifier.
+    pub id: String,
+    /// Nonce the client has to sign with its key.
+    pub nonce: String,
+    /// When the challenge expires, in seconds since the epoch.
+    pub expires_at: i64,
+}
+
+/// Sessions, persisted in an SQLite database.
+///
+/// A session starts out as a [`Challenge`]. Once its nonce is signed, it is authorized,
+/// and can be used with the bearer token returned by [`Sessions::authorize`]. Only a hash of
+/// the token is stored.
+#[derive(Clone)]
+pub struct Sessions {
+    db: Arc<sql::ConnectionThreadSafe>,
+}
+
+impl Sessions {
+    const SCHEMA: &'static str = r#"
+        create table if not exists "sessions" (
+          "id"          text     primary key not null,
+          "nonce"       text     not null,
+          "token"       text     unique,
+          "public_key"  text,
+          "issued_at"   integer  not null,
+          "expires_at"  integer  not null
+        ) strict;
+    "#;
+
+    /// Open the sessions database at the given path, creating it if needed.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, sql::Error> {
+        let mut db = sql::Connection::open_thread_safe(path)?;
+        db.set_busy_timeout(DB_TIMEOUT.as_millis() as usize)?;
+        db.execute(Self::SCHEMA)?;
+
+        let sessions = Self { db: Arc::new(db) };
+        sessions.prune(chrono::Utc::now().timestamp())?;
+
+        Ok(sessions)
+    }
+
+    /// Create a new session challenge.
+    ///
+    /// Returns `None` if there are already [`MAX_CHALLENGES`] pending challenges.
+    pub fn challenge(&self, now: i64) -> Result<Option<Challenge>, sql::Error> {
+        self.prune(now)?;
+
+        let mut stmt = self
+            .db
+            .prepare("SELECT COUNT(*) FROM sessions WHERE token IS NULL")?;
+        stmt.next()?;
+        if stmt.read::<i64, _>(0)? >= MAX_CHALLENGES {
+            return Ok(None);
+        }
+
+        let challenge = Challenge {
+            id: random(),
+            nonce: random(),
+            expires_at: now + CHALLENGE_TTL.as_secs() as i64,
+        };
+        let mut stmt = self.db.prepare(
+            "INSERT INTO sessions (id, nonce, issued_at, expires_at) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        stmt.bind((1, challenge.id.as_str()))?;
+        stmt.bind((2, challenge.nonce.as_str()))?;
+        stmt.bind((3, now))?;
+        stmt.bind((4, challenge.expires_at))?;
+        stmt.next()?;
+
+        Ok(Some(challenge))
+    }
+
+    /// Get a pending challenge by session id.
+    pub fn get_challenge(&self, id: &str, now: i64) -> Result<Option<Challenge>, sql::Error> {
+        let mut stmt = self.db.prepare(
+            "SELECT nonce, expires_at FROM sessions
+             WHERE id = ?1 AND token IS NULL AND expires_at > ?2",
+        )?;
+        stmt.bind((1, id))?;
+        stmt.bind((2, now))?;
+
+        if let Some(row) = stmt.into_iter().next() {
+            let row = row?;
+
+            return Ok(Some(Challenge {
+                id: id.to_owned(),
+                nonce: row.read::<&str, _>("nonce").to_owned(),
+                expires_at: row.read::<i64, _>("expires_at"),
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Authorize a pending session for the given key, returning the session and its bearer
+    /// token. The caller is expected to have verified the challenge signature.
+    ///
+    /// Returns `None` if there is no such pending session.
+    pub fn authorize(
+        &self,
+        id: &str,
+        public_key: PublicKey,
+        now: i64,
+    ) -> Result<Option<(Session, String)>, sql::Error> {
+        let token = random();
+        let session = Session {
+            id: id.to_owned(),
+            public_key,
+            issued_at: now,
+            expires_at: now + SESSION_TTL.as_secs() as i64,
+        };
+        let mut stmt = self.db.prepare(
+            "UPDATE sessions SET token = ?1, public_key = ?2, issued_at = ?3, expires_at = ?4
+             WHERE id = ?5 AND token IS NULL AND expires_at > ?3
+             RETURNING id",
+        )?;
+        stmt.bind((1, hash(&token).as_str()))?;
+        stmt.bind((2, session.public_key.to_string().as_str()))?;
+        stmt.bind((3, session.issued_at))?;
+        stmt.bind((4, session.expires_at))?;
+        stmt.bind((5, id))?;
+
+        // The connection is shared, so the number of changed rows is read from the statement
+        // itself rather than from the connection.
+        if stmt.next()? == sql::State::Done {
+            return Ok(None);
+        }
+        Ok(Some((session, token)))
+    }
+
+    /// Register an authorized session under the given bearer token.
+    #[cfg(test)]
+    pub fn insert(&self, token: String, session: Session) {
+        let mut stmt = self
+            .db
+            .prepare(
+                "INSERT INTO sessions (id, nonce, token, public_key, issued_at, expires_at)
+                 VALUES (?1, '', ?2, ?3, ?4, ?5)",
+            )
+            .unwrap();
+        stmt.bind((1, session.id.as_str())).unwrap();
+        stmt.bind((2, hash(&token).as_str())).unwrap();
+        stmt.bind((3, session.public_key.to_string().as_str()))
+            .unwrap();
+        stmt.bind((4, session.issued_at)).unwrap();
+        stmt.bind((5, session.expires_at)).unwrap();
+        stmt.next().unwrap();
+    }
+
+    /// Get a valid session by bearer token.
+    pub fn get(&self, token: &str) -> Result<Option<Session>, sql::Error> {
+        let now = chrono::Utc::now().timestamp();
+        let mut stmt = self.db.prepare(
+            "SELECT id, public_key, issued_at, expires_at FROM sessions
+             WHERE token = ?1 AND expires_at > ?2",
+        )?;
+        stmt.bind((1, hash(token).as_str()))?;
+        stmt.bind((2, now))?;
+
+        match stmt.into_iter().next() {
+            Some(row) => session(&row?).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// List the valid sessions of a key.
+    pub fn list(&self, public_key: &PublicKey) -> Result<Vec<Session>, sql::Error> {
+        let now = chrono::Utc::now().timestamp();
+        let mut stmt = self.db.prepare(
+            "SELECT id, public_key, issued_at, expires_at FROM sessions
+             WHERE public_key = ?1 AND token IS NOT NULL AND expires_at > ?2
+             ORDER BY issued_at DESC",
+        )?;
+        stmt.bind((1, public_key.to_string().as_str()))?;
+        stmt.bind((2, now))?;
+
+        stmt.into_iter().map(|row| session(&row?)).collect()
+    }
+
+    /// Revoke a session of the given key. Returns `false` if there was no such session.
+    pub fn revoke(&self, id: &str, public_key: &PublicKey) -> Result<bool, sql::Error> {
+        let mut stmt = self
+            .db
+            .prepare("DELETE FROM sessions WHERE id = ?1 AND public_key = ?2 RETURNING id")?;
+        stmt.bind((1, id))?;
+        stmt.bind((2, public_key.to_string().as_str()))?;
+
+        Ok(stmt.next()? == sql::State::Row)
+    }
+
+    /// Remove expired sessions and challenges.
+    fn prune(&self, now: i64) -> Result<(), sql::Error> {
+        let mut stmt = self
+            .db
+            .prepare("DELETE FROM sessions WHERE expires_at <= ?1")?;
+        stmt.bind((1, now))?;
+        stmt.next()?;
+
+        Ok(())
+    }
+}
+
+/// Extracts the session of a request from its `Authorization` header.
+///
+/// Rejects the request with `401 Unauthorized` if the header is missing, or if the token
+/// doesn't match a valid session. Use `Option<Session>` for routes that are also available
+/// without a session.
+#[async_trait]
+impl FromRequestParts<Context> for Session {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, ctx: &Context) -> Result<Self, Self::Rejection> {
+        ctx.sessions
+            .authenticate(&parts.headers)?
+            .ok_or(Error::Unauthorized)
+    }
+}
+
+impl Sessions {
+    /// Get the session of a request from its `Authorization` header.
+    ///
+    /// The session token is either passed as a bearer token, or as the password of HTTP basic
+    /// authentication, for clients such as `git` that don't support bearer tokens. The basic
+    /// authentication username is ignored.
+    pub fn authenticate(&self, headers: &HeaderMap) -> Result<Option<Session>, sql::Error> {
+        let Some(header) = headers.get(AUTHORIZATION).and_then(|h| h.to_str().ok()) else {
+            return Ok(None);
+        };
+        if let Some(token) = header.strip_prefix("Bearer ") {
+            return self.get(token.trim());
+        }
+        let credentials = header
+            .strip_prefix("Basic ")
+            .and_then(|c| BASE64_STANDARD.decode(c.trim()).ok())
+            .and_then(|c| String::from_utf8(c).ok());
+
+        match credentials.as_deref().and_then(|c| c.split_once(':')) {
+            Some((_, token)) => self.get(token),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Get the session of a request signed with an HTTP signature, following
+/// draft-cavage-http-signatures, eg.
+///
+/// ```text
+/// Date: Tue, 07 Jun 2024 20:51:35 GMT
+/// Signature: keyId="did:key:z6Mk…",algorithm="ed25519",headers="date",signature="<base64>"
+/// ```
+///
+/// The signature is made over the listed headers, one `<name>: <value>` line each, where the
+/// `(request-target)` pseudo-header is the lowercase method followed by the path and query.
+/// It has to cover the `Date` header, which must be within [`SIGNATURE_TTL`] of `now`, as
+/// well as `(request-target)` and `Host`, so that it can't be replayed against another request.
+/// Clients that can't sign every request, such as git, authenticate with a session token.
+///
+/// Returns `None` if the request has no `Signature` header.
+pub fn signed_session(
+    method: &Method,
+    uri: &Uri,
+    headers: &HeaderMap,
+    now: i64,
+) -> Result<Option<Session>, SignatureError> {
+    let Some(header) = headers.get("Signature") else {
+        return Ok(None);
+    };
+    let params = header
+        .to_str()
+        .map_err(|_| SignatureError::Malformed)?
+        .split(',')
+        .map(|param| {
+            let (key, value) = param.trim().split_once('=')?;
+            Some((key, value.trim_matches('"')))
+        })
+        .collect::<Option<HashMap<_, _>>>()
+        .ok_or(SignatureError::Malformed)?;
+
+    if !matches!(
+        params.get("algorithm"),
+        None | Some(&"ed25519") | Some(&"hs2019")
+    ) {
+        return Err(SignatureError::Malformed);
+    }
+    let key = params.get("keyId").ok_or(SignatureError::Malformed)?;
+    let key = match key.parse::<Did>() {
+        Ok(did) => *did,
+        Err(_) => key
+            .parse::<PublicKey>()
+            .map_err(|_| SignatureError::Malformed)?,
+    };
+    let signature = params
+        .get("signature")
+        .and_then(|s| BASE64_STANDARD.decode(s).ok())
+        .and_then(|s| Signature::try_from(s.as_slice()).ok())
+        .ok_or(SignatureError::Malformed)?;
+    let signed = params
+        .get("headers")
+        .map(|h| h.split_whitespace().collect::<Vec<_>>())
+        .unwrap_or_else(|| vec!["date"]);
+
+    if !signed.iter().any(|h| h.eq_ignore_ascii_case(DATE.as_str())) {
+        return Err(SignatureError::Date);
+    }
+    for required in ["(request-target)", "host"] {
+        if !signed.iter().any(|h| h.eq_ignore_ascii_case(required)) {
+            return Err(SignatureError::Uncovered(required));
+        }
+    }
+    let date = headers
+        .get(DATE)
+        .and_then(|d| d.to_str().ok())
+        .and_then(|d| chrono::DateTime::parse_from_rfc2822(d).ok())
+        .ok_or(SignatureError::Date)?
+        .timestamp();
+    if date.abs_diff(now) > SIGNATURE_TTL.as_secs() {
+        return Err(SignatureError::Date);
+    }
+
+    let mut message = Vec::new();
+    for name in signed {
+        let name = name.to_ascii_lowercase();
+        let value = if name == "(request-target)" {
+            let target = uri.path_and_query().map_or(uri.path(), |p| p.as_str());
+            format!("{} {target}", method.as_str().to_ascii_lowercase())
+        } else {
+            headers
+                .get(&name)
+                .and_then(|v| v.to_str().ok())
+                // HTTP/2 requests carry the host in the URI instead of a header.
+                .or_else(|| {
+                    uri.authority()
+                        .filter(|_| name == "host")
+                        .map(|a| a.as_str())
+                })
+                .ok_or_else(|| SignatureError::MissingHeader(name.clone()))?
+                .to_owned()
+        };
+        message.push(format!("{name}: {value}"));
+    }
+    key.verify(message.join("\n").as_bytes(), &signature)
+        .map_err(|_| SignatureError::Invalid)?;
+
+    Ok(Some(Session {
+        id: String::from("signature"),
+        public_key: key,
+        issued_at: date,
+        expires_at: date + SIGNATURE_TTL.as_secs() as i64,
+    }))
+}
+
+/// Whether a repository with the given identity document can be viewed with a session.
+///
+/// Public repositories can be viewed by anyone. Private repositories can only be viewed by
+/// their delegates and the DIDs in their allow-list.
+pub fn can_view(doc: &Doc, session: Option<&Session>) -> bool {
+    match session {
+        Some(session) => doc.is_visible_to(&session.public_key.into()),
+        None => doc.visibility().is_public(),
+    }
+}
+
+/// Read a session from a database row.
+fn session(row: &sql::Row) -> Result<Session, sql::Error> {
+    let public_key = row.read::<&str, _>("public_key").parse().map_err(
+        |e: radicle::crypto::PublicKeyError| sql::Error {
+            code: None,
+            message: Some(e.to_string()),
+        },
+    )?;
+
+    Ok(Session {
+        id: row.read::<&str, _>("id").to_owned(),
+        public_key,
+        issued_at: row.read::<i64, _>("issued_at"),
+        expires_at: row.read::<i64, _>("expires_at"),
+    })
+}
+
+/// Generate a random, URL-safe string.
+fn random() -> String {
+    let mut bytes = [0u8; RANDOM_BYTES];
+    #[allow(clippy::unwrap_used)]
+    getrandom::getrandom(&mut bytes).unwrap();
+
+    BASE64_URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Hash a bearer token for storage.
+fn hash(token: &str) -> String {
+    BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(token.as_bytes()))
+}
@@ -10,6 +10,22 @@ pub enum Error {
     #[error("entity not found")]
     NotFound,
 
+    /// The request is not authenticated.
+    #[error("unauthorized")]
+    Unauthorized,
+
+    /// The authenticated session is not allowed to perform this request.
+    #[error("forbidden")]
+    Forbidden,
+
+    /// The request was rejected to bound resource usage.
+    #[error("too many requests")]
+    TooManyRequests,
+
+    /// The request is invalid.
+    #[error("{0}")]
+    BadRequest(String),
+
     /// An error occurred with env variables.
     #[error(transparent)]
     Env(#[from] std::env::VarError),
@@ -50,6 +66,10 @@ pub enum Error {
     #[error(transparent)]
     CobStore(#[from] radicle::cob::store::Error),
 
+    /// Cob retrieval error.
+    #[error(transparent)]
+    CobRetrieve(#[from] radicle::cob::error::Retrieve),
+
     /// Repository error.
     #[error(transparent)]
     Repository(#[from] radicle::storage::RepositoryError),
@@ -93,6 +113,18 @@ pub enum Error {
     /// Node error.
     #[error(transparent)]
     Node(#[from] radicle::node::Error),
+
+    /// Regex error.
+    #[error(transparent)]
+    Regex(#[from] regex::Error),
+
+    /// I/O error.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// Sessions database error.
+    #[error(transparent)]
+    Sessions(#[from] sqlite::Error),
 }
 
 impl IntoResponse for Error {
@@ -100,6 +132,11 @@ impl IntoResponse for Error {
         let message = self.to_string();
         let (status, msg) = match self {
             Error::NotFound => (StatusCode::NOT_FOUND, None),
+            Error::Unauthorized => (StatusCode::UNAUTHORIZED, None),
+            Error::Forbidden => (StatusCode::FORBIDDEN, None),
+            Error::TooManyRequests => (StatusCode::TOO_MANY_REQUESTS, None),
+            Error::BadRequest(msg) => (StatusCode::BAD_REQUEST, Some(msg)),
+            Error::Regex(e) => (StatusCode::BAD_REQUEST, Some(e.to_string())),
             Error::CobStore(e @ radicle::cob::store::Error::NotFound(_, _)) => {
                 (StatusCode::NOT_FOUND, Some(e.to_string()))
             }
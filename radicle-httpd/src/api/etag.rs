@@ -0,0 +1,116 @@
+use std::fmt::Display;
+
+use axum::http::HeaderValue;
+use base64::prelude::BASE64_URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use radicle::cob::{ObjectId, TypeName};
+use radicle::identity::{DocAt, RepoId};
+use radicle::node::NodeId;
+use radicle::storage::git::Repository;
+
+use radicle::git::raw as git2;
+
+/// Builds a strong entity tag from the repository state a response is derived from.
+///
+/// The state is hashed, so the tag changes whenever any of it does. Node aliases aren't
+/// part of the repository state, responses that render them include them with
+/// [`ETag::aliases`].
+pub struct ETag {
+    hasher: Sha256,
+}
+
+impl ETag {
+    pub fn new(rid: RepoId) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(rid.to_string());
+
+        Self { hasher }
+    }
+
+    /// Include the identity document.
+    pub fn doc(mut self, doc: &DocAt) -> Self {
+        self.hasher.update(doc.commit.as_bytes());
+        self
+    }
+
+    /// Include the signed refs of every remote.
+    pub fn sigrefs(self, repo: &Repository) -> Result<Self, git2::Error> {
+        self.refs(repo, "refs/namespaces/*/refs/rad/sigrefs")
+    }
+
+    /// Include the signed refs of a single remote.
+    pub fn sigrefs_of(self, repo: &Repository, remote: &NodeId) -> Result<Self, git2::Error> {
+        self.refs(repo, &format!("refs/namespaces/{remote}/refs/rad/sigrefs"))
+    }
+
+    /// Include the canonical tags.
+    pub fn tags(self, repo: &Repository) -> Result<Self, git2::Error> {
+        self.refs(repo, "refs/tags/*")
+    }
+
+    /// Include the heads of all collaborative objects of a type, or of a single object.
+    pub fn cobs(
+        self,
+        repo: &Repository,
+        type_name: &TypeName,
+        id: Option<&ObjectId>,
+    ) -> Result<Self, git2::Error> {
+        let id = id.map(ToString::to_string).unwrap_or(String::from("*"));
+        self.refs(
+            repo,
+            &format!("refs/namespaces/*/refs/cobs/{type_name}/{id}"),
+        )
+    }
+
+    /// Include the aliases rendered in a response, eg. of authors.
+    pub fn aliases<'a>(self, values: impl IntoIterator<Item = &'a Value>) -> Self {
+        values.into_iter().fold(self, |etag, value| match value {
+            Value::Object(object) => {
+                let etag = match (object.get("id"), object.get("alias")) {
+                    (Some(id), Some(alias)) => etag.value(format!("{id}={alias}")),
+                    _ => etag,
+                };
+                etag.aliases(object.values())
+            }
+            Value::Array(values) => etag.aliases(values),
+            _ => etag,
+        })
+    }
+
+    /// Include any other value the response depends on.
+    pub fn value(mut self, value: impl Display) -> Self {
+        self.hasher.update(value.to_string());
+        self
+    }
+
+    /// Get the header value of the tag.
+    pub fn finish(self) -> HeaderValue {
+        let hash = BASE64_URL_SAFE_NO_PAD.encode(self.hasher.finalize());
+
+        // SAFETY: Quotes and URL-safe base64 are valid header value characters.
+        #[allow(clippy::unwrap_used)]
+        HeaderValue::from_str(&format!("\"{hash}\"")).unwrap()
+    }
+
+    /// Include the references matching a glob, and their targets.
+    fn refs(mut self, repo: &Repository, glob: &str) -> Result<Self, git2::Error> {
+        let mut refs = repo
+            .backend
+            .references_glob(glob)?
+            .filter_map(|r| {
+                let r = r.ok()?;
+                Some((r.name()?.to_owned(), r.target()?))
+            })
+            .collect::<Vec<_>>();
+        refs.sort();
+
+        for (name, oid) in refs {
+            self.hasher.update(name);
+            self.hasher.update(oid.as_bytes());
+        }
+        Ok(self)
+    }
+}
@@ -0,0 +1,439 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::{broadcast, oneshot};
+
+use radicle::cob::{issue, patch, ObjectId, TypeName};
+use radicle::git::{Oid, RefString};
+use radicle::identity::RepoId;
+use radicle::node::policy::SeedingPolicy;
+use radicle::node::{self, Handle as _, NodeId};
+use radicle::storage::ReadStorage;
+use radicle::{Node, Profile};
+
+/// How often storage and policies are scanned for changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+/// Number of events buffered per subscriber before it starts lagging.
+const CHANNEL_CAPACITY: usize = 256;
+
+/// A change to a repository, as streamed to `/events` subscribers.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum Event {
+    /// Git references changed under a remote.
+    #[serde(rename_all = "camelCase")]
+    RefsUpdated {
+        rid: RepoId,
+        remote: NodeId,
+        refs: Vec<RefChange>,
+    },
+    /// An issue was created.
+    #[serde(rename_all = "camelCase")]
+    IssueCreated {
+        rid: RepoId,
+        id: ObjectId,
+        remote: NodeId,
+    },
+    /// An issue was updated.
+    #[serde(rename_all = "camelCase")]
+    IssueUpdated {
+        rid: RepoId,
+        id: ObjectId,
+        remote: NodeId,
+    },
+    /// A patch was created.
+    #[serde(rename_all = "camelCase")]
+    PatchCreated {
+        rid: RepoId,
+        id: ObjectId,
+        remote: NodeId,
+    },
+    /// A patch was updated.
+    #[serde(rename_all = "camelCase")]
+    PatchUpdated {
+        rid: RepoId,
+        id: ObjectId,
+        remote: NodeId,
+    },
+    /// The seeding policy of a repository changed.
+    #[serde(rename_all = "camelCase")]
+    SeedingPolicyChanged {
+        rid: RepoId,
+        policy: Option<SeedingPolicy>,
+    },
+}
+
+impl Event {
+    /// The repository this event relates to.
+    pub fn rid(&self) -> RepoId {
+        match self {
+            Self::RefsUpdated { rid, .. }
+            | Self::IssueCreated { rid, .. }
+            | Self::IssueUpdated { rid, .. }
+            | Self::PatchCreated { rid, .. }
+            | Self::PatchUpdated { rid, .. }
+            | Self::SeedingPolicyChanged { rid, .. } => *rid,
+        }
+    }
+
+    /// The event name, as used for the SSE `event` field.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::RefsUpdated { .. } => "refsUpdated",
+            Self::IssueCreated { .. } => "issueCreated",
+            Self::IssueUpdated { .. } => "issueUpdated",
+            Self::PatchCreated { .. } => "patchCreated",
+            Self::PatchUpdated { .. } => "patchUpdated",
+            Self::SeedingPolicyChanged { .. } => "seedingPolicyChanged",
+        }
+    }
+}
+
+/// A single reference change. `old` is `None` for created references, `new` is `None` for
+/// deleted ones.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefChange {
+    pub name: RefString,
+    pub old: Option<Oid>,
+    pub new: Option<Oid>,
+}
+
+/// Broadcasts repository events to subscribers.
+///
+/// The underlying watcher runs on its own thread, and is only started while there is at
+/// least one subscriber. Events of private repositories are broadcast too, so subscribers
+/// have to check whether they can view the repository of each event.
+#[derive(Clone)]
+pub struct Events {
+    sender: broadcast::Sender<Event>,
+    running: Arc<AtomicBool>,
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+
+        Self {
+            sender,
+            running: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+impl Events {
+    /// Subscribe to events, starting the watcher if it isn't running.
+    ///
+    /// When starting the watcher, this waits for its initial snapshot, so that subsequent
+    /// changes are streamed.
+    pub async fn subscribe(
+        &self,
+        profile: Arc<Profile>,
+    ) -> Result<broadcast::Receiver<Event>, io::Error> {
+        let receiver = self.sender.subscribe();
+
+        if !self.running.swap(true, Ordering::SeqCst) {
+            let (sender, running) = (self.sender.clone(), self.running.clone());
+            let (ready, started) = oneshot::channel();
+            // The watcher thread owns its whole lifecycle, including the initial snapshot,
+            // which scans all of storage. This keeps it running if the subscriber goes away
+            // while waiting.
+            let spawned = thread::Builder::new()
+                .name(String::from("events"))
+                .spawn(move || {
+                    let _guard = Stopped(running.clone());
+                    let watcher = Watcher::new(profile, sender, running);
+
+                    ready.send(()).ok();
+                    watcher.run();
+                });
+            if let Err(err) = spawned {
+                self.running.store(false, Ordering::SeqCst);
+                return Err(err);
+            }
+            started.await.map_err(io::Error::other)?;
+        }
+        Ok(receiver)
+    }
+}
+
+/// Marks the watcher as stopped if its thread panics, so that the next subscriber starts a
+/// new one. Otherwise, the watcher marks itself as stopped once it has no subscribers.
+struct Stopped(Arc<AtomicBool>);
+
+impl Drop for Stopped {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.0.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Detects changes by diffing snapshots of storage references and seeding policies.
+///
+/// When the node is running, its event socket is used to find out which repositories to
+/// re-scan. Otherwise, all of storage is scanned every [`WATCH_INTERVAL`].
+struct Watcher {
+    profile: Arc<Profile>,
+    sender: broadcast::Sender<Event>,
+    running: Arc<AtomicBool>,
+    refs: HashMap<RepoId, BTreeMap<(NodeId, RefString), Oid>>,
+    policies: HashMap<RepoId, SeedingPolicy>,
+}
+
+impl Watcher {
+    fn new(
+        profile: Arc<Profile>,
+        sender: broadcast::Sender<Event>,
+        running: Arc<AtomicBool>,
+    ) -> Self {
+        let mut watcher = Self {
+            profile,
+            sender,
+            running,
+            refs: HashMap::new(),
+            policies: HashMap::new(),
+        };
+        // Take the initial snapshot, without emitting events for it.
+        watcher.scan_storage();
+        watcher.scan_policies();
+
+        watcher
+    }
+
+    fn run(mut self) {
+        tracing::debug!("events: watcher started");
+
+        loop {
+            match Node::new(self.profile.socket()).subscribe(WATCH_INTERVAL) {
+                Ok(events) => {
+                    tracing::debug!("events: subscribed to node events");
+
+                    let mut last_scan = Instant::now();
+                    for event in events {
+                        match event {
+                            Ok(node::Event::RefsFetched { rid, .. })
+                            | Ok(node::Event::LocalRefsAnnounced { rid, .. }) => {
+                                let events = self.scan_repo(rid);
+                                self.emit(events);
+                            }
+                            Ok(_) => {}
+                            Err(node::Error::TimedOut) => {}
+                            Err(err) => {
+                                tracing::debug!("events: node event error: {err}");
+                                break;
+                            }
+                        }
+                        if last_scan.elapsed() >= WATCH_INTERVAL {
+                            last_scan = Instant::now();
+
+                            let events = self.scan_policies();
+                            self.emit(events);
+                        }
+                        if !self.is_active() {
+                            return;
+                        }
+                    }
+                }
+                Err(_) => {
+                    thread::sleep(WATCH_INTERVAL);
+
+                    let mut events = self.scan_storage();
+                    events.extend(self.scan_policies());
+                    self.emit(events);
+                }
+            }
+            if !self.is_active() {
+                return;
+            }
+        }
+    }
+
+    /// Check whether there are still subscribers. If not, mark the watcher as stopped.
+    fn is_active(&self) -> bool {
+        if self.sender.receiver_count() > 0 {
+            return true;
+        }
+        self.running.store(false, Ordering::SeqCst);
+
+        // A subscriber may have arrived before we stopped, in which case we keep going.
+        if self.sender.receiver_count() > 0 && !self.running.swap(true, Ordering::SeqCst) {
+            return true;
+        }
+        tracing::debug!("events: watcher stopped");
+
+        false
+    }
+
+    fn emit(&self, events: Vec<Event>) {
+        for event in events {
+            // Only fails if there are no subscribers.
+            self.sender.send(event).ok();
+        }
+    }
+
+    fn scan_storage(&mut self) -> Vec<Event> {
+        let rids = match self.profile.storage.repositories() {
+            Ok(repos) => repos.into_iter().map(|r| r.rid).collect::<HashSet<_>>(),
+            Err(err) => {
+                tracing::error!("events: error listing repositories: {err}");
+                return vec![];
+            }
+        };
+        self.refs.retain(|rid, _| rids.contains(rid));
+
+        rids.into_iter()
+            .flat_map(|rid| self.scan_repo(rid))
+            .collect()
+    }
+
+    fn scan_repo(&mut self, rid: RepoId) -> Vec<Event> {
+        let Some(current) = self.snapshot(rid) else {
+            self.refs.remove(&rid);
+            return vec![];
+        };
+        let Some(previous) = self.refs.insert(rid, current.clone()) else {
+            return vec![];
+        };
+        diff(rid, &previous, &current)
+    }
+
+    /// Get all namespaced references of a repository.
+    fn snapshot(&self, rid: RepoId) -> Option<BTreeMap<(NodeId, RefString), Oid>> {
+        let repo = self.profile.storage.repository(rid).ok()?;
+        let mut refs = BTreeMap::new();
+
+        for r in repo.backend.references_glob("refs/namespaces/*").ok()? {
+            let Ok(r) = r else {
+                continue;
+            };
+            let (Some(name), Some(oid)) = (r.name(), r.target()) else {
+                continue;
+            };
+            let Some((remote, name)) = name
+                .strip_prefix("refs/namespaces/")
+                .and_then(|s| s.split_once('/'))
+            else {
+                continue;
+            };
+            let (Ok(remote), Ok(name)) = (remote.parse(), RefString::try_from(name)) else {
+                continue;
+            };
+            refs.insert((remote, name), oid.into());
+        }
+        Some(refs)
+    }
+
+    fn scan_policies(&mut self) -> Vec<Event> {
+        let current = match self.profile.policies() {
+            Ok(policies) => match policies.seed_policies() {
+                Ok(policies) => policies
+                    .map(|p| (p.rid, p.policy))
+                    .collect::<HashMap<_, _>>(),
+                Err(err) => {
+                    tracing::error!("events: error listing policies: {err}");
+                    return vec![];
+                }
+            },
+            Err(err) => {
+                tracing::error!("events: error opening policies: {err}");
+                return vec![];
+            }
+        };
+        let previous = std::mem::replace(&mut self.policies, current);
+        let mut events = Vec::new();
+
+        for (rid, policy) in &self.policies {
+            if previous.get(rid) != Some(policy) {
+                events.push(Event::SeedingPolicyChanged {
+                    rid: *rid,
+                    policy: Some(*policy),
+                });
+            }
+        }
+        for rid in previous.keys() {
+            if !self.policies.contains_key(rid) {
+                events.push(Event::SeedingPolicyChanged {
+                    rid: *rid,
+                    policy: None,
+                });
+            }
+        }
+        events
+    }
+}
+
+/// Compute the events between two reference snapshots of a repository.
+fn diff(
+    rid: RepoId,
+    previous: &BTreeMap<(NodeId, RefString), Oid>,
+    current: &BTreeMap<(NodeId, RefString), Oid>,
+) -> Vec<Event> {
+    let mut changes: BTreeMap<NodeId, Vec<RefChange>> = BTreeMap::new();
+    let mut events = Vec::new();
+
+    for ((remote, name), new) in current {
+        let old = previous.get(&(*remote, name.clone()));
+        if old == Some(new) {
+            continue;
+        }
+        if let Some((typename, id)) = cob(name) {
+            // A COB is only new if no remote had it before.
+            let existed = previous.keys().any(|(_, n)| n == name);
+            let (rid, id, remote) = (rid, id, *remote);
+
+            if typename == *issue::TYPENAME {
+                events.push(if existed {
+                    Event::IssueUpdated { rid, id, remote }
+                } else {
+                    Event::IssueCreated { rid, id, remote }
+                });
+            } else if typename == *patch::TYPENAME {
+                events.push(if existed {
+                    Event::PatchUpdated { rid, id, remote }
+                } else {
+                    Event::PatchCreated { rid, id, remote }
+                });
+            }
+            continue;
+        }
+        if is_tracked(name) {
+            changes.entry(*remote).or_default().push(RefChange {
+                name: name.clone(),
+                old: old.copied(),
+                new: Some(*new),
+            });
+        }
+    }
+    for ((remote, name), old) in previous {
+        if !current.contains_key(&(*remote, name.clone())) && is_tracked(name) {
+            changes.entry(*remote).or_default().push(RefChange {
+                name: name.clone(),
+                old: Some(*old),
+                new: None,
+            });
+        }
+    }
+    for (remote, refs) in changes {
+        events.push(Event::RefsUpdated { rid, remote, refs });
+    }
+    events
+}
+
+/// Parse a COB reference of the form `refs/cobs/<typename>/<id>`.
+fn cob(name: &RefString) -> Option<(TypeName, ObjectId)> {
+    let (typename, id) = name.as_str().strip_prefix("refs/cobs/")?.split_once('/')?;
+
+    Some((typename.parse().ok()?, id.parse().ok()?))
+}
+
+/// Whether a reference change is reported as part of [`Event::RefsUpdated`].
+/// COBs have their own events, and signed refs change with every other reference.
+fn is_tracked(name: &RefString) -> bool {
+    !name.as_str().starts_with("refs/cobs/") && name.as_str() != "refs/rad/sigrefs"
+}
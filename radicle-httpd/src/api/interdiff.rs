@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use radicle::git::raw as git2;
+use radicle_surf as surf;
+
+/// How a commit of a revision relates to the commits of another revision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// Same changes and message in both revisions.
+    Unchanged,
+    /// Matched in both revisions, with different changes or message.
+    Modified,
+    /// Only in the newer revision.
+    Added,
+    /// Only in the older revision.
+    Removed,
+}
+
+impl Status {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unchanged => "unchanged",
+            Self::Modified => "modified",
+            Self::Added => "added",
+            Self::Removed => "removed",
+        }
+    }
+}
+
+/// A commit of the older revision, paired with a commit of the newer one.
+pub struct Pair {
+    pub status: Status,
+    pub old: Option<git2::Oid>,
+    pub new: Option<git2::Oid>,
+    /// For modified commits, how the changes of the old commit differ from the changes of the
+    /// new commit. For added or removed commits, the changes of the commit.
+    pub diff: Option<surf::diff::Diff>,
+}
+
+/// A commit and its changes, as compared across revisions.
+struct Change {
+    oid: git2::Oid,
+    message: String,
+    summary: String,
+    patch_id: git2::Oid,
+    /// Hunks of each file changed, without line numbers, which shift whenever the base does.
+    files: BTreeMap<PathBuf, String>,
+}
+
+/// Compute the range-diff between two ranges of commits, each given by its base and head.
+///
+/// Commits are paired when they make the same changes, or otherwise when they have the same
+/// summary. Pairs are in the order of the newer range, with the commits only in the older range
+/// placed before the first pair that follows them.
+pub fn range_diff(
+    repo: &git2::Repository,
+    old: (git2::Oid, git2::Oid),
+    new: (git2::Oid, git2::Oid),
+) -> Result<Vec<Pair>, surf::Error> {
+    let old = changes(repo, old)?;
+    let new = changes(repo, new)?;
+    let mut matches: Vec<Option<usize>> = vec![None; new.len()];
+    let mut matched = vec![false; old.len()];
+
+    let criteria: [fn(&Change, &Change) -> bool; 2] = [
+        |a, b| a.patch_id == b.patch_id,
+        |a, b| a.summary == b.summary,
+    ];
+    for same in criteria {
+        for (i, n) in new.iter().enumerate() {
+            if matches[i].is_some() {
+                continue;
+            }
+            if let Some(j) = (0..old.len()).find(|&j| !matched[j] && same(&old[j], n)) {
+                matches[i] = Some(j);
+                matched[j] = true;
+            }
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let mut next = 0;
+    for (n, m) in new.iter().zip(matches) {
+        let Some(j) = m else {
+            pairs.push(Pair {
+                status: Status::Added,
+                old: None,
+                new: Some(n.oid),
+                diff: Some(commit_diff(repo, n.oid)?),
+            });
+            continue;
+        };
+        for k in next..j {
+            if !matched[k] {
+                pairs.push(removed(repo, &old[k])?);
+            }
+        }
+        next = next.max(j + 1);
+
+        let o = &old[j];
+        let (status, diff) = if o.patch_id == n.patch_id && o.message == n.message {
+            (Status::Unchanged, None)
+        } else {
+            (Status::Modified, Some(interdiff(o, n)?))
+        };
+        pairs.push(Pair {
+            status,
+            old: Some(o.oid),
+            new: Some(n.oid),
+            diff,
+        });
+    }
+    for k in next..old.len() {
+        if !matched[k] {
+            pairs.push(removed(repo, &old[k])?);
+        }
+    }
+    Ok(pairs)
+}
+
+fn removed(repo: &git2::Repository, change: &Change) -> Result<Pair, surf::Error> {
+    Ok(Pair {
+        status: Status::Removed,
+        old: Some(change.oid),
+        new: None,
+        diff: Some(commit_diff(repo, change.oid)?),
+    })
+}
+
+/// The commits between a base and a head, oldest first, with their changes.
+fn changes(
+    repo: &git2::Repository,
+    (base, head): (git2::Oid, git2::Oid),
+) -> Result<Vec<Change>, git2::Error> {
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+    walk.push(head)?;
+    walk.hide(base)?;
+
+    walk.map(|oid| {
+        let commit = repo.find_commit(oid?)?;
+        let diff = diff(repo, &commit)?;
+        let mut files = BTreeMap::<PathBuf, String>::new();
+
+        diff.print(git2::DiffFormat::Patch, |delta, _, line| {
+            let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+                return true;
+            };
+            let content = String::from_utf8_lossy(line.content());
+            let file = files.entry(path.to_path_buf()).or_default();
+            match line.origin() {
+                'H' => {
+                    // Only keep the section heading that follows the line numbers.
+                    let heading = content.splitn(3, "@@").nth(2).unwrap_or("\n");
+                    file.push_str("@@");
+                    file.push_str(heading);
+                }
+                origin @ ('+' | '-' | ' ') => {
+                    file.push(origin);
+                    file.push_str(&content);
+                    if !content.ends_with('\n') {
+                        file.push('\n');
+                    }
+                }
+                _ => {}
+            }
+            true
+        })?;
+
+        Ok(Change {
+            oid: commit.id(),
+            message: commit.message().unwrap_or_default().to_owned(),
+            summary: commit.summary().unwrap_or_default().to_owned(),
+            patch_id: diff.patchid(None)?,
+            files,
+        })
+    })
+    .collect()
+}
+
+/// The diff of a commit against its first parent.
+fn diff<'a>(
+    repo: &'a git2::Repository,
+    commit: &git2::Commit,
+) -> Result<git2::Diff<'a>, git2::Error> {
+    let parent = commit.parents().next().map(|p| p.tree()).transpose()?;
+    let mut diff = repo.diff_tree_to_tree(parent.as_ref(), Some(&commit.tree()?), None)?;
+    diff.find_similar(None)?;
+
+    Ok(diff)
+}
+
+fn commit_diff(repo: &git2::Repository, oid: git2::Oid) -> Result<surf::diff::Diff, surf::Error> {
+    let commit = repo.find_commit(oid)?;
+    let diff = diff(repo, &commit)?;
+
+    Ok(surf::diff::Diff::try_from(diff)?)
+}
+
+/// The diff of the changes of two commits, file by file.
+fn interdiff(old: &Change, new: &Change) -> Result<surf::diff::Diff, surf::Error> {
+    let mut paths = old.files.keys().chain(new.files.keys()).collect::<Vec<_>>();
+    paths.sort();
+    paths.dedup();
+
+    let mut buffer = Vec::new();
+    for path in paths {
+        let a = old.files.get(path).map(String::as_str).unwrap_or_default();
+        let b = new.files.get(path).map(String::as_str).unwrap_or_default();
+        if a == b {
+            continue;
+        }
+        let mut patch =
+            git2::Patch::from_buffers(a.as_bytes(), Some(path), b.as_bytes(), Some(path), None)?;
+        buffer.extend_from_slice(&patch.to_buf()?);
+    }
+    let diff = git2::Diff::from_buffer(&buffer)?;
+
+    Ok(surf::diff::Diff::try_from(diff)?)
+}
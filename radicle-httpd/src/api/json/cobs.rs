@@ -1,10 +1,12 @@
 use std::collections::BTreeMap;
 
+use base64::Engine as _;
+use nonempty::NonEmpty;
 use radicle_surf as surf;
 use serde_json::{json, Value};
 
 use radicle::cob;
-use radicle::cob::{issue, patch};
+use radicle::cob::{identity as identity_cob, issue, patch};
 use radicle::identity;
 use radicle::node::AliasStore;
 use radicle::storage::{git, refs, RemoteRepository};
@@ -34,6 +36,24 @@ impl<'a> Issue<'a> {
             "labels": self.0.labels().collect::<Vec<_>>(),
         })
     }
+
+    /// Summary for list views, without the discussion.
+    pub fn as_summary_json(&self, id: issue::IssueId, aliases: &impl AliasStore) -> Value {
+        json!({
+            "id": id.to_string(),
+            "author": Author::new(self.0.author().id()).as_json(aliases),
+            "title": self.0.title(),
+            "state": self.0.state(),
+            "assignees": self.0.assignees().map(|assignee|
+                Author::new(assignee).as_json(aliases)
+            ).collect::<Vec<_>>(),
+            "labels": self.0.labels().collect::<Vec<_>>(),
+            "timestamp": self.0.timestamp().as_secs(),
+            "counts": {
+                "comments": self.0.replies().count(),
+            },
+        })
+    }
 }
 
 pub(crate) struct Patch<'a>(&'a patch::Patch);
@@ -70,6 +90,33 @@ impl<'a> Patch<'a> {
             ).collect::<Vec<_>>(),
         })
     }
+
+    /// Summary for list views, with the latest revision only and without discussions or
+    /// reviews.
+    pub fn as_summary_json(&self, id: patch::PatchId, aliases: &impl AliasStore) -> Value {
+        let (latest_id, latest) = self.0.latest();
+        let (comments, reviews) = self.0.revisions().fold((0, 0), |(c, r), (_, rev)| {
+            (c + rev.discussion().len(), r + rev.reviews().count())
+        });
+
+        json!({
+            "id": id.to_string(),
+            "author": Author::new(self.0.author().id()).as_json(aliases),
+            "title": self.0.title(),
+            "state": self.0.state(),
+            "target": self.0.target(),
+            "labels": self.0.labels().collect::<Vec<_>>(),
+            "assignees": self.0.assignees().map(|assignee|
+                Author::new(&assignee).as_json(aliases)
+            ).collect::<Vec<_>>(),
+            "latestRevision": Revision::new(latest).as_summary_json(latest_id, aliases),
+            "counts": {
+                "revisions": self.0.revisions().count(),
+                "comments": comments,
+                "reviews": reviews,
+            },
+        })
+    }
 }
 
 pub(crate) struct Revision<'a>(&'a patch::Revision);
@@ -123,6 +170,18 @@ impl<'a> Revision<'a> {
             ).collect::<Vec<_>>(),
         })
     }
+
+    /// Summary for list views, without discussions, reviews or refs.
+    pub fn as_summary_json(&self, id: patch::RevisionId, aliases: &impl AliasStore) -> Value {
+        json!({
+            "id": id,
+            "author": Author::new(self.0.author().id()).as_json(aliases),
+            "description": self.0.description(),
+            "base": self.0.base(),
+            "oid": self.0.head(),
+            "timestamp": self.0.timestamp().as_secs(),
+        })
+    }
 }
 
 pub(crate) struct Review<'a>(&'a patch::Review);
@@ -146,6 +205,148 @@ impl<'a> Review<'a> {
     }
 }
 
+/// A collaborative object of any type, as stored.
+pub(crate) struct Cob<'a>(&'a cob::CollaborativeObject<NonEmpty<cob::Entry>>);
+
+impl<'a> Cob<'a> {
+    pub fn new(cob: &'a cob::CollaborativeObject<NonEmpty<cob::Entry>>) -> Self {
+        Self(cob)
+    }
+
+    /// Summary for list views, without the change history.
+    pub fn as_summary_json(&self, aliases: &impl AliasStore) -> Value {
+        let root = self.0.object().first();
+
+        json!({
+            "id": self.0.id(),
+            "typeName": self.0.typename(),
+            "manifest": self.0.manifest(),
+            "author": Author::new(&root.signature.key.into()).as_json(aliases),
+            "timestamp": root.timestamp,
+            "heads": self.0.history().tips(),
+            "changes": self.0.object().len(),
+        })
+    }
+
+    /// The object with its changes, in the order they are applied.
+    pub fn as_json(&self, aliases: &impl AliasStore) -> Value {
+        json!({
+            "id": self.0.id(),
+            "typeName": self.0.typename(),
+            "manifest": self.0.manifest(),
+            "heads": self.0.history().tips(),
+            "changes": self.0.object().iter().map(|entry| json!({
+                "id": entry.id,
+                "author": Author::new(&entry.signature.key.into()).as_json(aliases),
+                "timestamp": entry.timestamp,
+                "parents": entry.parents,
+                "related": entry.related,
+                "resource": entry.resource,
+                "manifest": entry.manifest,
+                "actions": entry.contents.iter().map(|c| action(c)).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Action payloads are JSON for all known object types. Others are returned as base64.
+fn action(contents: &[u8]) -> Value {
+    serde_json::from_slice(contents)
+        .unwrap_or_else(|_| json!({ "base64": base64::prelude::BASE64_STANDARD.encode(contents) }))
+}
+
+pub(crate) struct IdentityRevision<'a> {
+    revision: &'a identity_cob::Revision,
+    parent: Option<&'a identity_cob::Revision>,
+}
+
+impl<'a> IdentityRevision<'a> {
+    /// A revision, and the revision it is based on, if any.
+    pub fn new(
+        revision: &'a identity_cob::Revision,
+        parent: Option<&'a identity_cob::Revision>,
+    ) -> Self {
+        Self { revision, parent }
+    }
+
+    pub fn as_json(&self, aliases: &impl AliasStore) -> Result<Value, radicle::git::raw::Error> {
+        let doc = &self.revision.doc;
+        let parent = self.parent.map(|p| &p.doc);
+        let delegates = doc.delegates();
+        let (added, removed) = match parent {
+            Some(parent) => (
+                delegates
+                    .iter()
+                    .filter(|d| !parent.delegates().contains(d))
+                    .collect::<Vec<_>>(),
+                parent
+                    .delegates()
+                    .iter()
+                    .filter(|d| !delegates.contains(d))
+                    .collect::<Vec<_>>(),
+            ),
+            None => (delegates.iter().collect(), vec![]),
+        };
+        let threshold = parent
+            .filter(|p| p.threshold() != doc.threshold())
+            .map(|p| json!({ "from": p.threshold(), "to": doc.threshold() }));
+        let visibility = parent
+            .filter(|p| p.visibility() != doc.visibility())
+            .map(|p| json!({ "from": p.visibility(), "to": doc.visibility() }));
+        let diff = self.diff()?;
+
+        Ok(json!({
+            "id": self.revision.id,
+            "title": self.revision.title,
+            "description": self.revision.description,
+            "author": Author::new(self.revision.author.id()).as_json(aliases),
+            "state": self.revision.state,
+            "parent": self.revision.parent,
+            "timestamp": self.revision.timestamp.as_secs(),
+            "doc": doc,
+            "verdicts": self.revision.verdicts().map(|(key, verdict)| {
+                let (verdict, signature) = match verdict {
+                    identity_cob::Verdict::Accept(sig) => ("accept", Some(sig.to_string())),
+                    identity_cob::Verdict::Reject => ("reject", None),
+                };
+                json!({
+                    "author": Author::new(&identity::Did::from(key)).as_json(aliases),
+                    "verdict": verdict,
+                    "signature": signature,
+                })
+            }).collect::<Vec<_>>(),
+            "changes": {
+                "delegates": {
+                    "added": added.into_iter().map(|d| Author::new(d).as_json(aliases)).collect::<Vec<_>>(),
+                    "removed": removed.into_iter().map(|d| Author::new(d).as_json(aliases)).collect::<Vec<_>>(),
+                },
+                "threshold": threshold,
+                "visibility": visibility,
+            },
+            "diff": diff,
+        }))
+    }
+
+    /// Unified diff between the pretty-printed documents of the parent and this revision.
+    fn diff(&self) -> Result<String, radicle::git::raw::Error> {
+        let pretty =
+            |doc: &identity::Doc| serde_json::to_string_pretty(doc).unwrap_or_default() + "\n";
+        let old = self.parent.map(|p| pretty(&p.doc)).unwrap_or_default();
+        let new = pretty(&self.revision.doc);
+        let path = std::path::Path::new("radicle.json");
+        let mut patch = radicle::git::raw::Patch::from_buffers(
+            old.as_bytes(),
+            Some(path),
+            new.as_bytes(),
+            Some(path),
+            None,
+        )?;
+        let diff = patch.to_buf()?;
+
+        Ok(diff.as_str().unwrap_or_default().to_owned())
+    }
+}
+
 fn get_refs(
     repo: &git::Repository,
     id: &cob::ActorId,
@@ -5,66 +5,118 @@ use base64::{prelude::BASE64_STANDARD, Engine};
 use radicle_surf as surf;
 use serde_json::{json, Value};
 
-pub(crate) struct Commit<'a>(&'a surf::Commit);
+use radicle::git::raw as git2;
+use radicle::identity::Did;
+use radicle::node::AliasStore;
+
+use super::Author;
+use crate::api::signature;
+
+pub(crate) struct Commit<'a> {
+    commit: &'a surf::Commit,
+    signature: Option<signature::Signature>,
+}
 
 impl<'a> Commit<'a> {
     pub fn new(commit: &'a surf::Commit) -> Self {
-        Self(commit)
+        Self {
+            commit,
+            signature: None,
+        }
     }
 
+    /// Include the signature of the commit, verified against the repository.
+    pub fn signed(mut self, repo: &git2::Repository) -> Result<Self, git2::Error> {
+        self.signature = signature::Signature::of_commit(repo, self.commit.id.into())?;
+        Ok(self)
+    }
+
+    /// Signers are only identified by their DID, see [`Commit::with_signer`].
     pub fn as_json(&self) -> Value {
         json!({
-            "id": self.0.id,
+            "id": self.commit.id,
             "author": {
-                "name": self.0.author.name,
-                "email": self.0.author.email
+                "name": self.commit.author.name,
+                "email": self.commit.author.email
             },
-            "summary": self.0.summary,
-            "description": self.0.description(),
-            "parents": self.0.parents,
+            "summary": self.commit.summary,
+            "description": self.commit.description(),
+            "parents": self.commit.parents,
             "committer": {
-                "name": self.0.committer.name,
-                "email": self.0.committer.email,
-                "time": self.0.committer.time.seconds()
-            }
+                "name": self.commit.committer.name,
+                "email": self.commit.committer.email,
+                "time": self.commit.committer.time.seconds()
+            },
+            "signature": self.signature.as_ref().map(|s| Signature::new(s).as_json()),
         })
     }
+
+    /// Add the alias of the signer to the JSON of a commit, as returned by [`Commit::as_json`].
+    /// Returns whether the commit has a signer.
+    pub fn with_signer(json: &mut Value, aliases: &impl AliasStore) -> bool {
+        json.get_mut("signature")
+            .is_some_and(|signature| Signature::with_signer(signature, aliases))
+    }
 }
 
-pub(crate) struct Blob<'a, T: AsRef<[u8]>>(&'a surf::blob::Blob<T>);
+pub(crate) struct Blob<'a, T: AsRef<[u8]>> {
+    blob: &'a surf::blob::Blob<T>,
+    last_commit: Commit<'a>,
+}
 
 impl<'a, T: AsRef<[u8]>> Blob<'a, T> {
     pub fn new(blob: &'a surf::blob::Blob<T>) -> Self {
-        Self(blob)
+        Self {
+            blob,
+            last_commit: Commit::new(blob.commit()),
+        }
+    }
+
+    /// Include the signature of the last commit, verified against the repository.
+    pub fn signed(mut self, repo: &git2::Repository) -> Result<Self, git2::Error> {
+        self.last_commit = self.last_commit.signed(repo)?;
+        Ok(self)
     }
 
     pub fn as_json(&self, path: &str) -> Value {
-        let content = match str::from_utf8(self.0.content()) {
+        let content = match str::from_utf8(self.blob.content()) {
             Ok(s) => s.to_owned(),
-            Err(_) => BASE64_STANDARD.encode(self.0.content()),
+            Err(_) => BASE64_STANDARD.encode(self.blob.content()),
         };
 
         json!({
-            "binary": self.0.is_binary(),
+            "binary": self.blob.is_binary(),
             "name": name_in_path(path),
             "content": content,
             "path": path,
-            "lastCommit": Commit(self.0.commit()).as_json()
+            "lastCommit": self.last_commit.as_json()
         })
     }
 }
 
-pub(crate) struct Tree<'a>(&'a surf::tree::Tree);
+pub(crate) struct Tree<'a> {
+    tree: &'a surf::tree::Tree,
+    last_commit: Commit<'a>,
+}
 
 impl<'a> Tree<'a> {
     pub fn new(tree: &'a surf::tree::Tree) -> Self {
-        Self(tree)
+        Self {
+            tree,
+            last_commit: Commit::new(tree.commit()),
+        }
+    }
+
+    /// Include the signature of the last commit, verified against the repository.
+    pub fn signed(mut self, repo: &git2::Repository) -> Result<Self, git2::Error> {
+        self.last_commit = self.last_commit.signed(repo)?;
+        Ok(self)
     }
 
     pub fn as_json(&self, path: &str) -> Value {
         let prefix = Path::new(path);
         let entries = self
-            .0
+            .tree
             .entries()
             .iter()
             .map(|entry| {
@@ -83,13 +135,52 @@ impl<'a> Tree<'a> {
 
         json!({
             "entries": &entries,
-            "lastCommit": Commit::new(self.0.commit()).as_json(),
+            "lastCommit": self.last_commit.as_json(),
             "name": name_in_path(path),
             "path": path,
         })
     }
 }
 
+pub(crate) struct Signature<'a>(&'a signature::Signature);
+
+impl<'a> Signature<'a> {
+    pub fn new(signature: &'a signature::Signature) -> Self {
+        Self(signature)
+    }
+
+    /// Signers are only identified by their DID, see [`Signature::with_signer`].
+    pub fn as_json(&self) -> Value {
+        json!({
+            "type": self.0.kind.as_str(),
+            "status": self.0.status.as_str(),
+            "signer": self.0.key.map(|key| json!({ "id": Did::from(key) })),
+        })
+    }
+
+    /// Add the alias of the signer, and whether it's known, to the JSON of a signature, as
+    /// returned by [`Signature::as_json`]. Returns whether the signature has a signer.
+    ///
+    /// Aliases can change, so they are left out of the JSON of commits, trees and blobs, which
+    /// is immutable and can be cached.
+    pub fn with_signer(json: &mut Value, aliases: &impl AliasStore) -> bool {
+        let Some(signature) = json.as_object_mut() else {
+            return false;
+        };
+        let signer = signature
+            .get("signer")
+            .and_then(|s| s.get("id")?.as_str()?.parse::<Did>().ok());
+        let known = signer.is_some_and(|did| aliases.alias(&did).is_some());
+
+        if let Some(did) = &signer {
+            signature.insert("signer".to_owned(), Author::new(did).as_json(aliases));
+        }
+        signature.insert("known".to_owned(), known.into());
+
+        signer.is_some()
+    }
+}
+
 /// Returns the name part of a path string.
 fn name_in_path(path: &str) -> &str {
     match path.rsplit('/').next() {
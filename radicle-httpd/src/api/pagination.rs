@@ -0,0 +1,262 @@
+use std::fmt;
+use std::str::FromStr;
+
+use axum::http::{header, HeaderMap, HeaderValue, Uri};
+use serde::Serialize;
+
+use radicle::cob::ObjectId;
+
+use crate::api::error::Error;
+
+/// Max number of items returned per page, whatever the page size requested. Endpoints may
+/// return larger pages when no page size is requested.
+pub const MAX_PER_PAGE: usize = 100;
+
+/// A page of a list.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Number of items in the whole list, unless counting them would mean reading it all,
+    /// see [`Pagination::stream`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    /// Opaque cursor to pass as `cursor` to get the next page, if any.
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Transform the items of the page.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            next_cursor: self.next_cursor,
+        }
+    }
+
+    /// Transform the items of the page, failing if any item can't be transformed.
+    pub fn try_map<U, E>(self, f: impl FnMut(T) -> Result<U, E>) -> Result<Page<U>, E> {
+        Ok(Page {
+            items: self.items.into_iter().map(f).collect::<Result<_, _>>()?,
+            total: self.total,
+            next_cursor: self.next_cursor,
+        })
+    }
+
+    /// Transform the items of the page, dropping the items that can't be transformed.
+    /// Dropped items are still counted in the total.
+    pub fn filter_map<U>(self, f: impl FnMut(T) -> Option<U>) -> Page<U> {
+        Page {
+            items: self.items.into_iter().filter_map(f).collect(),
+            total: self.total,
+            next_cursor: self.next_cursor,
+        }
+    }
+
+    /// Get the RFC 8288 `Link` header to the next page, given the URI of this page.
+    pub fn link(&self, uri: &Uri) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let Some(cursor) = &self.next_cursor else {
+            return headers;
+        };
+        let mut query = uri
+            .query()
+            .and_then(|q| serde_urlencoded::from_str::<Vec<(String, String)>>(q).ok())
+            .unwrap_or_default();
+        query.retain(|(k, _)| k != "cursor" && k != "page");
+        query.push((String::from("cursor"), cursor.clone()));
+
+        let Ok(query) = serde_urlencoded::to_string(query) else {
+            return headers;
+        };
+        if let Ok(link) = HeaderValue::from_str(&format!("<{}?{query}>; rel=\"next\"", uri.path()))
+        {
+            headers.insert(header::LINK, link);
+        }
+        headers
+    }
+}
+
+/// Which page of a list is requested.
+///
+/// Pages are either requested by cursor, which is stable when items are added to the list,
+/// or by page number.
+pub struct Pagination {
+    cursor: Option<String>,
+    page: usize,
+    per_page: usize,
+}
+
+impl Pagination {
+    pub fn new(
+        cursor: Option<String>,
+        page: Option<usize>,
+        per_page: Option<usize>,
+        default_per_page: usize,
+    ) -> Self {
+        Self {
+            cursor,
+            page: page.unwrap_or(0),
+            per_page: per_page.map_or(default_per_page, |n| n.min(MAX_PER_PAGE)),
+        }
+    }
+
+    /// Get the page of a list sorted by key. The cursor is the key of the last item of the
+    /// previous page, which doesn't need to still be in the list.
+    #[allow(clippy::result_large_err)]
+    pub fn sorted<K, T>(&self, items: Vec<(K, T)>) -> Result<Page<T>, Error>
+    where
+        K: Ord + fmt::Display + FromStr,
+    {
+        let start = match &self.cursor {
+            Some(cursor) => {
+                let cursor = Self::parse::<K>(cursor)?;
+                items.partition_point(|(k, _)| *k <= cursor)
+            }
+            None => self.page.saturating_mul(self.per_page),
+        };
+        Ok(self.page(items, start))
+    }
+
+    /// Get the page of a list that isn't sorted by key, eg. a commit history. The cursor is the
+    /// key of the last item of the previous page, which must still be in the list.
+    #[allow(clippy::result_large_err)]
+    pub fn walk<K, T>(&self, items: Vec<(K, T)>) -> Result<Page<T>, Error>
+    where
+        K: PartialEq + fmt::Display + FromStr,
+    {
+        let start = match &self.cursor {
+            Some(cursor) => {
+                let cursor = Self::parse::<K>(cursor)?;
+                items
+                    .iter()
+                    .position(|(k, _)| *k == cursor)
+                    .map(|i| i + 1)
+                    .ok_or_else(|| Error::BadRequest(String::from("cursor not found")))?
+            }
+            None => self.page.saturating_mul(self.per_page),
+        };
+        Ok(self.page(items, start))
+    }
+
+    /// Get the page of a list that isn't sorted by key, like [`Pagination::walk`], reading the
+    /// list only up to the item after the page. The rest of the list is only read to count
+    /// its items if `count` is set, otherwise the total is unknown.
+    #[allow(clippy::result_large_err)]
+    pub fn stream<K, T>(
+        &self,
+        items: impl IntoIterator<Item = Result<(K, T), Error>>,
+        count: bool,
+    ) -> Result<Page<T>, Error>
+    where
+        K: PartialEq + fmt::Display + FromStr,
+    {
+        let mut items = items.into_iter();
+        let mut skipped = 0;
+
+        match &self.cursor {
+            Some(cursor) => {
+                let cursor = Self::parse::<K>(cursor)?;
+                loop {
+                    let Some(item) = items.next() else {
+                        return Err(Error::BadRequest(String::from("cursor not found")));
+                    };
+                    skipped += 1;
+                    if item?.0 == cursor {
+                        break;
+                    }
+                }
+            }
+            None => {
+                for item in items.by_ref().take(self.page.saturating_mul(self.per_page)) {
+                    item?;
+                    skipped += 1;
+                }
+            }
+        }
+        let page = items
+            .by_ref()
+            .take(self.per_page)
+            .collect::<Result<Vec<_>, _>>()?;
+        let more = items.next().transpose()?.is_some();
+        let total = if count {
+            let rest = items.try_fold(0, |n, item| item.map(|_| n + 1))?;
+            Some(skipped + page.len() + usize::from(more) + rest)
+        } else {
+            None
+        };
+        let next_cursor = if more {
+            page.last().map(|(k, _)| k.to_string())
+        } else {
+            None
+        };
+
+        Ok(Page {
+            items: page.into_iter().map(|(_, t)| t).collect(),
+            total,
+            next_cursor,
+        })
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn parse<K: FromStr>(cursor: &str) -> Result<K, Error> {
+        cursor
+            .parse()
+            .map_err(|_| Error::BadRequest(format!("invalid cursor '{cursor}'")))
+    }
+
+    fn page<K: fmt::Display, T>(&self, items: Vec<(K, T)>, start: usize) -> Page<T> {
+        let total = items.len();
+        let items = items
+            .into_iter()
+            .skip(start)
+            .take(self.per_page)
+            .collect::<Vec<_>>();
+        let next_cursor = if start.saturating_add(self.per_page) < total {
+            items.last().map(|(k, _)| k.to_string())
+        } else {
+            None
+        };
+
+        Page {
+            items: items.into_iter().map(|(_, t)| t).collect(),
+            total: Some(total),
+            next_cursor,
+        }
+    }
+}
+
+/// Key of a collaborative object in a list sorted by some value, eg. its creation time.
+/// Objects with the same value are sorted by id.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CobKey {
+    value: i64,
+    id: ObjectId,
+}
+
+impl CobKey {
+    pub fn new(id: ObjectId, value: i64) -> Self {
+        Self { value, id }
+    }
+}
+
+impl fmt::Display for CobKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.value, self.id)
+    }
+}
+
+impl FromStr for CobKey {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The value may be negative, but the id never contains a dash.
+        let (value, id) = s.rsplit_once('-').ok_or(())?;
+
+        Ok(Self {
+            value: value.parse().map_err(|_| ())?,
+            id: id.parse().map_err(|_| ())?,
+        })
+    }
+}
@@ -1,14 +1,34 @@
-use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
-use radicle::cob::{issue, patch};
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+
+use radicle::cob::issue::{self, Issue};
+use radicle::cob::patch::{self, MergeTarget, Patch, Verdict};
+use radicle::cob::{Label, Timestamp};
+use radicle::git::Oid;
+use radicle::identity::{Did, RepoId};
 use radicle::node::policy::Scope;
 use radicle::node::NodeId;
 
+use crate::api::auth::Session;
+use crate::api::error::Error;
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PaginationQuery {
     #[serde(default)]
     pub show: RepoQuery,
+    pub cursor: Option<String>,
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PageQuery {
+    pub cursor: Option<String>,
     pub page: Option<usize>,
     pub per_page: Option<usize>,
 }
@@ -29,10 +49,344 @@ pub struct RawQuery {
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
-pub struct CobsQuery<T> {
+pub struct ArchiveQuery {
+    /// Only archive this subdirectory.
+    pub path: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CobsQuery<T, S = CobSort> {
+    pub cursor: Option<String>,
     pub page: Option<usize>,
     pub per_page: Option<usize>,
     pub status: Option<T>,
+    /// Only list objects with all of these labels, comma-separated.
+    pub labels: Option<List<Label>>,
+    pub author: Option<Did>,
+    pub assignee: Option<Did>,
+    /// Only list objects mentioning this DID, or the session's DID if `me`.
+    pub mentions: Option<Mention>,
+    /// Creation time range, in seconds since the epoch.
+    pub created_since: Option<i64>,
+    pub created_until: Option<i64>,
+    /// Time range of the latest comment or edit, in seconds since the epoch.
+    pub updated_since: Option<i64>,
+    pub updated_until: Option<i64>,
+    /// Only list objects whose title or description contain all of these words.
+    pub q: Option<String>,
+    pub sort: Option<S>,
+    pub order: Option<SortOrder>,
+    #[serde(default)]
+    pub view: View,
+}
+
+impl<T, S> CobsQuery<T, S> {
+    /// Get the filter matching the objects to list.
+    #[allow(clippy::result_large_err)]
+    pub fn filter(&self, session: Option<&Session>) -> Result<CobFilter, Error> {
+        let mentions = match &self.mentions {
+            Some(Mention::Me) => {
+                let session = session.ok_or(Error::Unauthorized)?;
+                Some(Did::from(session.public_key))
+            }
+            Some(Mention::Did(did)) => Some(*did),
+            None => None,
+        };
+        let terms = self
+            .q
+            .as_deref()
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+
+        Ok(CobFilter {
+            labels: self.labels.clone().map(|l| l.0).unwrap_or_default(),
+            author: self.author,
+            assignee: self.assignee,
+            mentions,
+            created: (self.created_since, self.created_until),
+            updated: (self.updated_since, self.updated_until),
+            terms,
+        })
+    }
+}
+
+/// A comma-separated list of values in a query string.
+#[derive(Debug, Clone)]
+pub struct List<T>(pub Vec<T>);
+
+impl<'de, T> Deserialize<'de> for List<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.split(',')
+            .filter(|v| !v.is_empty())
+            .map(|v| v.parse().map_err(de::Error::custom))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl<T: fmt::Display> Serialize for List<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let values = self.0.iter().map(ToString::to_string).collect::<Vec<_>>();
+        serializer.serialize_str(&values.join(","))
+    }
+}
+
+/// Who a collaborative object mentions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Mention {
+    /// The DID of the session.
+    Me,
+    Did(Did),
+}
+
+impl TryFrom<String> for Mention {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        if s == "me" {
+            return Ok(Self::Me);
+        }
+        s.parse()
+            .map(Self::Did)
+            .map_err(|_| format!("invalid mention '{s}', expected a DID or 'me'"))
+    }
+}
+
+impl From<Mention> for String {
+    fn from(mention: Mention) -> Self {
+        match mention {
+            Mention::Me => String::from("me"),
+            Mention::Did(did) => did.to_string(),
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum CobSort {
+    #[default]
+    Created,
+    /// Time of the latest comment or edit.
+    Updated,
+    Comments,
+}
+
+impl CobSort {
+    /// Get the value to sort an object by.
+    pub fn value(&self, cob: &CobFields) -> i64 {
+        match self {
+            Self::Created => cob.created.as_millis() as i64,
+            Self::Updated => cob.updated.as_millis() as i64,
+            Self::Comments => cob.comments as i64,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum PatchSort {
+    #[default]
+    Created,
+    /// Time of the latest comment, edit or review.
+    Updated,
+    Comments,
+    /// Time of the latest revision.
+    Revision,
+}
+
+impl PatchSort {
+    /// Get the value to sort a patch by.
+    pub fn value(&self, patch: &Patch, fields: &CobFields) -> i64 {
+        match self {
+            Self::Created => CobSort::Created.value(fields),
+            Self::Updated => CobSort::Updated.value(fields),
+            Self::Comments => CobSort::Comments.value(fields),
+            Self::Revision => patch.updated_at().as_millis() as i64,
+        }
+    }
+}
+
+/// How much of each item a list endpoint renders.
+#[derive(Default, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum View {
+    /// Items as rendered by their own endpoint.
+    #[default]
+    Full,
+    /// Counts instead of threads, for list views.
+    Summary,
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    /// Get the key to sort a list by ascending key, given the value to sort by.
+    pub fn key(&self, value: i64) -> i64 {
+        match self {
+            Self::Asc => value,
+            Self::Desc => -value,
+        }
+    }
+}
+
+/// The fields of a collaborative object that filters and sorts are applied to.
+pub struct CobFields<'a> {
+    pub title: &'a str,
+    pub description: &'a str,
+    pub author: Did,
+    pub labels: Vec<&'a Label>,
+    pub assignees: Vec<Did>,
+    /// Bodies of all comments, the description included.
+    pub bodies: Vec<&'a str>,
+    /// Number of comments, the description excluded. For patches, comments on reviews
+    /// aren't counted, as in the counts of patch summaries.
+    pub comments: usize,
+    pub created: Timestamp,
+    /// Time of the latest comment or edit.
+    pub updated: Timestamp,
+}
+
+impl<'a> From<&'a Issue> for CobFields<'a> {
+    fn from(issue: &'a Issue) -> Self {
+        let comments = issue.comments().map(|(_, c)| c).collect::<Vec<_>>();
+        let updated = comments
+            .iter()
+            .flat_map(|c| c.edits())
+            .map(|e| e.timestamp)
+            .max()
+            .unwrap_or(issue.timestamp());
+
+        Self {
+            title: issue.title(),
+            description: issue.description(),
+            author: *issue.author().id(),
+            labels: issue.labels().collect(),
+            assignees: issue.assignees().copied().collect(),
+            bodies: comments.iter().map(|c| c.body()).collect(),
+            comments: comments.len().saturating_sub(1),
+            created: issue.timestamp(),
+            updated,
+        }
+    }
+}
+
+impl<'a> From<&'a Patch> for CobFields<'a> {
+    fn from(patch: &'a Patch) -> Self {
+        let mut bodies = Vec::new();
+        let mut comments = 0;
+        let mut updated = patch.timestamp();
+
+        for (_, revision) in patch.revisions() {
+            bodies.push(revision.description());
+            for edit in revision.edits() {
+                updated = updated.max(edit.timestamp);
+            }
+            for (_, comment) in revision.discussion().comments() {
+                bodies.push(comment.body());
+                comments += 1;
+                for edit in comment.edits() {
+                    updated = updated.max(edit.timestamp);
+                }
+            }
+            for (_, review) in revision.reviews() {
+                bodies.extend(review.summary());
+                updated = updated.max(review.timestamp());
+                for (_, comment) in review.comments() {
+                    bodies.push(comment.body());
+                }
+            }
+        }
+
+        Self {
+            title: patch.title(),
+            description: patch.description(),
+            author: *patch.author().id(),
+            labels: patch.labels().collect(),
+            assignees: patch.assignees().collect(),
+            bodies,
+            comments,
+            created: patch.timestamp(),
+            updated,
+        }
+    }
+}
+
+/// Filters collaborative objects, see [`CobsQuery`].
+pub struct CobFilter {
+    labels: Vec<Label>,
+    author: Option<Did>,
+    assignee: Option<Did>,
+    mentions: Option<Did>,
+    created: (Option<i64>, Option<i64>),
+    updated: (Option<i64>, Option<i64>),
+    /// Lowercase words of the free-text query.
+    terms: Vec<String>,
+}
+
+impl CobFilter {
+    pub fn matches(&self, cob: &CobFields) -> bool {
+        let in_range = |(since, until): (Option<i64>, Option<i64>), time: Timestamp| {
+            let time = (time.as_millis() / 1000) as i64;
+            since.is_none_or(|s| time >= s) && until.is_none_or(|u| time < u)
+        };
+
+        if !self.labels.iter().all(|l| cob.labels.contains(&l)) {
+            return false;
+        }
+        if self.author.is_some_and(|a| a != cob.author) {
+            return false;
+        }
+        if self.assignee.is_some_and(|a| !cob.assignees.contains(&a)) {
+            return false;
+        }
+        if let Some(did) = &self.mentions {
+            // Mentions are written as DIDs, or as bare NIDs.
+            let nid = did.as_key().to_string();
+            if !cob
+                .bodies
+                .iter()
+                .chain([&cob.title])
+                .any(|b| b.contains(&nid))
+            {
+                return false;
+            }
+        }
+        if !in_range(self.created, cob.created) || !in_range(self.updated, cob.updated) {
+            return false;
+        }
+        if !self.terms.is_empty() {
+            let title = cob.title.to_lowercase();
+            let description = cob.description.to_lowercase();
+
+            return self
+                .terms
+                .iter()
+                .all(|t| title.contains(t) || description.contains(t));
+        }
+        true
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EventsQuery {
+    /// Only stream events of this repository.
+    pub rid: Option<RepoId>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -62,6 +416,106 @@ impl IssueStatus {
     }
 }
 
+/// Filters specific to patch listings, on top of the [`CobsQuery`] filters.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchQuery {
+    /// Only list patches targeting this branch, eg. `master`.
+    pub target: Option<String>,
+    /// Only list patches reviewed by this DID. Combined with `verdict`, only list patches
+    /// whose latest revision got this verdict from them.
+    pub reviewer: Option<Did>,
+    /// Verdict of the reviews of the latest revision.
+    pub verdict: Option<ReviewVerdict>,
+    /// Only list patches with, or without, unresolved comment threads.
+    pub unresolved: Option<bool>,
+    /// Only list patches with a revision based on this commit.
+    pub base: Option<Oid>,
+    /// Only list patches with a revision pointing to this commit.
+    pub head: Option<Oid>,
+}
+
+impl PatchQuery {
+    /// Whether a patch matches the query, given the default branch of the project.
+    pub fn matches(&self, patch: &Patch, default_branch: Option<&str>) -> bool {
+        if let Some(target) = &self.target {
+            // Patches are merged by the delegates, into the default branch.
+            let branch = match patch.target() {
+                MergeTarget::Delegates => default_branch,
+            };
+            let target = target.strip_prefix("refs/heads/").unwrap_or(target);
+            if branch != Some(target) {
+                return false;
+            }
+        }
+        let (_, latest) = patch.latest();
+        let verdicts = latest
+            .reviews()
+            .filter(|(pk, _)| self.reviewer.is_none_or(|r| r == Did::from(**pk)))
+            .filter_map(|(_, r)| r.verdict())
+            .collect::<Vec<_>>();
+
+        if let Some(reviewer) = &self.reviewer {
+            let reviewed = patch
+                .revisions()
+                .any(|(_, r)| r.reviews().any(|(pk, _)| reviewer == &Did::from(*pk)));
+            if !reviewed && !matches!(self.verdict, Some(ReviewVerdict::Pending)) {
+                return false;
+            }
+        }
+        if let Some(verdict) = &self.verdict {
+            if !verdict.matches(&verdicts) {
+                return false;
+            }
+        }
+        if let Some(unresolved) = self.unresolved {
+            let has_unresolved = patch.revisions().any(|(_, revision)| {
+                revision
+                    .discussion()
+                    .comments()
+                    .chain(revision.reviews().flat_map(|(_, r)| r.comments()))
+                    .any(|(_, c)| c.reply_to().is_none() && !c.is_resolved())
+            });
+            if has_unresolved != unresolved {
+                return false;
+            }
+        }
+        if let Some(base) = &self.base {
+            if !patch.revisions().any(|(_, r)| r.base() == base) {
+                return false;
+            }
+        }
+        if let Some(head) = &self.head {
+            if !patch.revisions().any(|(_, r)| r.head() == *head) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum ReviewVerdict {
+    /// Accepted, and not rejected by anyone.
+    Accepted,
+    /// Rejected by at least one reviewer.
+    Rejected,
+    /// Without accepting or rejecting reviews.
+    Pending,
+}
+
+impl ReviewVerdict {
+    fn matches(&self, verdicts: &[Verdict]) -> bool {
+        let rejected = verdicts.contains(&Verdict::Reject);
+        match self {
+            Self::Accepted => !rejected && verdicts.contains(&Verdict::Accept),
+            Self::Rejected => rejected,
+            Self::Pending => verdicts.is_empty(),
+        }
+    }
+}
+
 #[derive(Default, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum PatchStatus {
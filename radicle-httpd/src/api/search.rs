@@ -0,0 +1,423 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::{Mutex, RwLock, RwLockReadGuard};
+
+use radicle::git::Oid;
+use radicle::identity::doc::{Doc, Payload, PayloadId};
+use radicle::identity::RepoId;
+use radicle::node::routing::Store;
+use radicle::node::{AliasStore, Database};
+use radicle::profile::Aliases;
+use radicle::storage::{self, ReadRepository, ReadStorage, RepositoryInfo};
+use radicle::{Profile, Storage};
+
+use crate::api::error::Error;
+use crate::api::README_PATHS;
+
+/// Max number of README bytes that are indexed per repository.
+const MAX_README_SIZE: usize = 1_048_576;
+
+/// BM25 term frequency saturation parameter.
+const K1: f64 = 1.2;
+/// BM25 document length normalization parameter.
+const B: f64 = 0.75;
+/// Score multiplier applied when a query term only matches the prefix of an indexed term.
+const PREFIX_PENALTY: f64 = 0.5;
+/// How long the index is used before it is refreshed.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchQueryString {
+    pub q: Option<String>,
+    pub cursor: Option<String>,
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+}
+
+/// A searchable field of a repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Field {
+    Name,
+    Alias,
+    Description,
+    Readme,
+}
+
+impl Field {
+    /// How much a match in this field contributes to the final score.
+    fn weight(&self) -> f64 {
+        match self {
+            Self::Name => 4.0,
+            Self::Alias => 2.0,
+            Self::Description => 1.5,
+            Self::Readme => 1.0,
+        }
+    }
+}
+
+/// An indexed repository.
+struct Document {
+    /// Head of the default branch at the time of indexing.
+    head: Oid,
+    /// Identity document at the time of indexing.
+    doc: Doc,
+    /// Delegate aliases at the time of indexing.
+    aliases: Vec<String>,
+    /// Number of terms per field.
+    lengths: HashMap<Field, usize>,
+}
+
+/// Search index shared between requests.
+///
+/// Searches are served under a read lock. Refreshes scan storage on a blocking thread, and only
+/// take the write lock to apply the changes they found.
+#[derive(Clone, Default)]
+pub struct Search {
+    index: Arc<RwLock<Index>>,
+    /// When the index was last refreshed, if ever. Held for the duration of a refresh.
+    refreshed: Arc<Mutex<Option<Instant>>>,
+    /// Whether the index was built at least once.
+    ready: Arc<AtomicBool>,
+}
+
+impl Search {
+    /// Get the index for searching.
+    ///
+    /// The first search waits for the index to be built. After that, an index older than
+    /// [`REFRESH_INTERVAL`] is refreshed in the background, and searches are served from the
+    /// current index in the meantime.
+    pub async fn index(&self, profile: Arc<Profile>) -> Result<RwLockReadGuard<'_, Index>, Error> {
+        let refreshed = if self.ready.load(Ordering::Acquire) {
+            self.refreshed.clone().try_lock_owned().ok()
+        } else {
+            Some(self.refreshed.clone().lock_owned().await)
+        };
+
+        if let Some(mut refreshed) = refreshed {
+            match *refreshed {
+                None => {
+                    self.refresh(profile).await?;
+                    *refreshed = Some(Instant::now());
+                    self.ready.store(true, Ordering::Release);
+                }
+                Some(at) if at.elapsed() >= REFRESH_INTERVAL => {
+                    let search = self.clone();
+
+                    tokio::spawn(async move {
+                        if let Err(err) = search.refresh(profile).await {
+                            tracing::error!("search: error refreshing index: {err}");
+                        }
+                        *refreshed = Some(Instant::now());
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(self.index.read().await)
+    }
+
+    async fn refresh(&self, profile: Arc<Profile>) -> Result<(), Error> {
+        let index = self.index.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let aliases = profile.aliases();
+            let update = index.blocking_read().update(&profile.storage, &aliases)?;
+            index.blocking_write().apply(update);
+
+            Ok(())
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+}
+
+/// Changes to bring an [`Index`] up to date with storage.
+pub struct Update {
+    /// Repositories to (re-)index, with their delegate aliases and README.
+    insert: Vec<(RepositoryInfo, Vec<String>, Option<String>)>,
+    /// Repositories to drop from the index.
+    remove: Vec<RepoId>,
+}
+
+/// Inverted index over repository names, descriptions, READMEs and delegate aliases.
+#[derive(Default)]
+pub struct Index {
+    documents: HashMap<RepoId, Document>,
+    /// Maps a term to the repositories containing it, with per-field term frequencies.
+    postings: BTreeMap<String, HashMap<RepoId, HashMap<Field, usize>>>,
+}
+
+impl Index {
+    /// Find the changes needed to bring the index up to date with storage.
+    ///
+    /// Only repositories that are new, or whose default branch head, identity document or
+    /// delegate aliases changed since the last refresh are re-indexed. Repositories that were
+    /// removed from storage or made private are dropped from the index.
+    pub fn update(&self, storage: &Storage, aliases: &Aliases) -> Result<Update, storage::Error> {
+        let mut seen = HashSet::new();
+        let mut insert = Vec::new();
+
+        for info in storage.repositories()? {
+            if info.doc.visibility().is_private() {
+                continue;
+            }
+            seen.insert(info.rid);
+
+            let delegates = delegate_aliases(&info.doc, aliases);
+            if let Some(existing) = self.documents.get(&info.rid) {
+                if existing.head == info.head
+                    && existing.doc == info.doc
+                    && existing.aliases == delegates
+                {
+                    continue;
+                }
+            }
+            let readme = storage
+                .repository(info.rid)
+                .ok()
+                .and_then(|repo| readme(&repo, info.head));
+
+            insert.push((info, delegates, readme));
+        }
+
+        let remove = self
+            .documents
+            .keys()
+            .filter(|rid| !seen.contains(rid))
+            .copied()
+            .collect::<Vec<_>>();
+
+        Ok(Update { insert, remove })
+    }
+
+    /// Apply changes found by [`Index::update`].
+    pub fn apply(&mut self, update: Update) {
+        for rid in update.remove {
+            self.remove(&rid);
+        }
+        for (info, delegates, readme) in update.insert {
+            self.remove(&info.rid);
+            self.insert(info, delegates, readme.as_deref());
+        }
+    }
+
+    /// Search the index, returning matching repositories ordered by relevance.
+    ///
+    /// Every query term has to match at least one field of a repository for it to be
+    /// returned. Terms match indexed terms exactly, or by prefix with a lower score.
+    /// Results with equal scores are ordered by seeding count.
+    pub fn search(&self, q: &str, db: &Database, aliases: &Aliases) -> Vec<SearchResult> {
+        let terms = tokenize(q);
+        let mut scores: HashMap<RepoId, f64> =
+            self.documents.keys().map(|rid| (*rid, 0.0)).collect();
+
+        for term in terms {
+            let mut matched: HashMap<RepoId, f64> = HashMap::new();
+
+            for (indexed, postings) in self
+                .postings
+                .range(term.clone()..)
+                .take_while(|(indexed, _)| indexed.starts_with(&term))
+            {
+                let penalty = if *indexed == term {
+                    1.0
+                } else {
+                    PREFIX_PENALTY
+                };
+                let idf = self.idf(postings.len());
+
+                for (rid, freqs) in postings {
+                    let score = freqs
+                        .iter()
+                        .map(|(field, tf)| penalty * idf * self.bm25(rid, *field, *tf))
+                        .sum::<f64>();
+                    let best = matched.entry(*rid).or_default();
+                    *best = best.max(score);
+                }
+            }
+            scores.retain(|rid, score| match matched.get(rid) {
+                Some(s) => {
+                    *score += s;
+                    true
+                }
+                None => false,
+            });
+        }
+
+        let mut results = scores
+            .into_iter()
+            .filter_map(|(rid, score)| {
+                let doc = self.documents.get(&rid)?;
+                Some(SearchResult::new(rid, &doc.doc, score, db, aliases))
+            })
+            .collect::<Vec<_>>();
+        results.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b.seeds.cmp(&a.seeds))
+                .then(a.rid.cmp(&b.rid))
+        });
+
+        results
+    }
+
+    fn insert(&mut self, info: RepositoryInfo, aliases: Vec<String>, readme: Option<&str>) {
+        let mut fields = vec![(Field::Alias, aliases.join(" "))];
+        if let Ok(project) = info.doc.project() {
+            fields.push((Field::Name, project.name().to_owned()));
+            fields.push((Field::Description, project.description().to_owned()));
+        }
+        if let Some(readme) = readme {
+            fields.push((Field::Readme, readme.to_owned()));
+        }
+
+        let mut lengths = HashMap::new();
+        for (field, text) in fields {
+            let terms = tokenize(&text);
+            lengths.insert(field, terms.len());
+
+            for term in terms {
+                *self
+                    .postings
+                    .entry(term)
+                    .or_default()
+                    .entry(info.rid)
+                    .or_default()
+                    .entry(field)
+                    .or_default() += 1;
+            }
+        }
+
+        self.documents.insert(
+            info.rid,
+            Document {
+                head: info.head,
+                doc: info.doc,
+                aliases,
+                lengths,
+            },
+        );
+    }
+
+    fn remove(&mut self, rid: &RepoId) {
+        if self.documents.remove(rid).is_none() {
+            return;
+        }
+        self.postings.retain(|_, postings| {
+            postings.remove(rid);
+            !postings.is_empty()
+        });
+    }
+
+    /// Inverse document frequency of a term found in `n` repositories.
+    fn idf(&self, n: usize) -> f64 {
+        let total = self.documents.len() as f64;
+        let n = n as f64;
+
+        (1.0 + (total - n + 0.5) / (n + 0.5)).ln()
+    }
+
+    /// Weighted BM25 term score for a field of a repository.
+    fn bm25(&self, rid: &RepoId, field: Field, tf: usize) -> f64 {
+        let len = self
+            .documents
+            .get(rid)
+            .and_then(|d| d.lengths.get(&field))
+            .copied()
+            .unwrap_or_default() as f64;
+        let avg = self.average_length(field);
+        let tf = tf as f64;
+        let norm = if avg > 0.0 { len / avg } else { 1.0 };
+
+        field.weight() * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * norm))
+    }
+
+    fn average_length(&self, field: Field) -> f64 {
+        if self.documents.is_empty() {
+            return 0.0;
+        }
+        let total = self
+            .documents
+            .values()
+            .filter_map(|d| d.lengths.get(&field))
+            .sum::<usize>();
+
+        total as f64 / self.documents.len() as f64
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SearchResult {
+    pub rid: RepoId,
+    pub payloads: BTreeMap<PayloadId, Payload>,
+    pub delegates: Vec<serde_json::Value>,
+    pub seeds: usize,
+    #[serde(skip)]
+    pub score: f64,
+}
+
+impl SearchResult {
+    fn new(rid: RepoId, doc: &Doc, score: f64, db: &Database, aliases: &Aliases) -> Self {
+        let seeds = db.count(&rid).unwrap_or_default();
+        let delegates = doc
+            .delegates()
+            .iter()
+            .map(|did| match aliases.alias(did) {
+                Some(alias) => json!({
+                    "id": did,
+                    "alias": alias,
+                }),
+                None => json!({
+                    "id": did,
+                }),
+            })
+            .collect::<Vec<_>>();
+
+        SearchResult {
+            rid,
+            payloads: doc.payload().clone(),
+            delegates,
+            seeds,
+            score,
+        }
+    }
+}
+
+/// Split text into lowercase alphanumeric terms.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+/// Resolve the aliases of a repository's delegates.
+fn delegate_aliases(doc: &Doc, aliases: &Aliases) -> Vec<String> {
+    doc.delegates()
+        .iter()
+        .filter_map(|did| aliases.alias(did))
+        .map(|alias| alias.to_string())
+        .collect()
+}
+
+/// Read the README of a repository at the given commit.
+fn readme<R: ReadRepository>(repo: &R, head: Oid) -> Option<String> {
+    README_PATHS
+        .iter()
+        .map(ToString::to_string)
+        .chain(README_PATHS.iter().map(|p| p.to_lowercase()))
+        .find_map(|path| {
+            let blob = repo.blob_at(head, &path).ok()?;
+            let content = blob.content();
+            let content = &content[..content.len().min(MAX_README_SIZE)];
+
+            Some(String::from_utf8_lossy(content).into_owned())
+        })
+}
@@ -0,0 +1,138 @@
+use radicle::crypto;
+use radicle::git::raw as git2;
+
+/// Namespace of the SSH signatures made by git.
+const GIT_NAMESPACE: &str = "git";
+/// Start of an SSH signature armor.
+const SSH_ARMOR: &str = "-----BEGIN SSH SIGNATURE-----";
+/// Start of a PGP signature armor.
+const PGP_ARMOR: &str = "-----BEGIN PGP SIGNATURE-----";
+
+/// Kind of key a git object was signed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Ssh,
+    Gpg,
+}
+
+impl Kind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ssh => "ssh",
+            Self::Gpg => "gpg",
+        }
+    }
+}
+
+/// Outcome of verifying a signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The signature was made by its key, over the signed object.
+    Valid,
+    /// The signature doesn't match the signed object, or can't be decoded.
+    Invalid,
+    /// The signature can't be verified, eg. GPG signatures, or SSH keys other than Ed25519.
+    Unverified,
+}
+
+impl Status {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Valid => "valid",
+            Self::Invalid => "invalid",
+            Self::Unverified => "unverified",
+        }
+    }
+}
+
+/// Signature of a git object.
+#[derive(Debug, Clone)]
+pub struct Signature {
+    pub kind: Kind,
+    pub status: Status,
+    /// Key the object was signed with, for SSH signatures made with Ed25519 keys.
+    pub key: Option<crypto::PublicKey>,
+}
+
+impl Signature {
+    /// Verify an armored signature of a payload.
+    pub fn verify(signature: &[u8], payload: &[u8]) -> Option<Self> {
+        if signature.starts_with(PGP_ARMOR.as_bytes()) {
+            return Some(Self {
+                kind: Kind::Gpg,
+                status: Status::Unverified,
+                key: None,
+            });
+        }
+        if !signature.starts_with(SSH_ARMOR.as_bytes()) {
+            return None;
+        }
+        let Ok(sig) = ssh_key::SshSig::from_pem(signature) else {
+            return Some(Self {
+                kind: Kind::Ssh,
+                status: Status::Invalid,
+                key: None,
+            });
+        };
+        let Some(key) = sig.public_key().ed25519() else {
+            return Some(Self {
+                kind: Kind::Ssh,
+                status: Status::Unverified,
+                key: None,
+            });
+        };
+        let key = crypto::PublicKey::from(key.0);
+        let valid = sig.namespace() == GIT_NAMESPACE
+            && ssh_key::SshSig::signed_data(GIT_NAMESPACE, sig.hash_alg(), payload)
+                .ok()
+                .zip(crypto::Signature::try_from(sig.signature().as_bytes()).ok())
+                .is_some_and(|(data, sig)| key.verify(data, &sig).is_ok());
+
+        Some(Self {
+            kind: Kind::Ssh,
+            status: if valid {
+                Status::Valid
+            } else {
+                Status::Invalid
+            },
+            key: Some(key),
+        })
+    }
+
+    /// Get the signature of a commit, if it is signed.
+    pub fn of_commit(repo: &git2::Repository, oid: git2::Oid) -> Result<Option<Self>, git2::Error> {
+        match repo.extract_signature(&oid, None) {
+            Ok((signature, payload)) => Ok(Self::verify(&signature, &payload)),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the signature of an annotated tag, if it is signed.
+    ///
+    /// Tag signatures are appended to the tag object, which is signed up to the signature.
+    pub fn of_tag(repo: &git2::Repository, oid: git2::Oid) -> Result<Option<Self>, git2::Error> {
+        let odb = repo.odb()?;
+        let object = odb.read(oid)?;
+        let data = object.data();
+
+        Ok(signature_start(data).and_then(|i| Self::verify(&data[i..], &data[..i])))
+    }
+}
+
+/// Strip the signature from the message of an annotated tag.
+pub fn tag_message(message: &str) -> &str {
+    signature_start(message.as_bytes()).map_or(message, |i| &message[..i])
+}
+
+/// Find the signature trailing a tag: like git, the last armor that starts a line.
+fn signature_start(data: &[u8]) -> Option<usize> {
+    [SSH_ARMOR, PGP_ARMOR]
+        .iter()
+        .filter_map(|armor| {
+            (0..=data.len().checked_sub(armor.len())?).rev().find(|&i| {
+                data[i..].starts_with(armor.as_bytes()) && (i == 0 || data[i - 1] == b'\n')
+            })
+        })
+        .max()
+}
@@ -1,15 +1,18 @@
 mod delegates;
+mod events;
 mod node;
 mod repos;
+mod sessions;
 mod stats;
 
 use axum::extract::State;
 use axum::response::{IntoResponse, Json};
 use axum::routing::get;
-use axum::Router;
+use axum::{middleware, Router};
 use serde_json::json;
 
 use crate::api::{Context, API_VERSION, RADICLE_VERSION};
+use crate::axum_extra;
 
 pub fn router(ctx: Context) -> Router {
     let root_router = Router::new()
@@ -20,8 +23,11 @@ pub fn router(ctx: Context) -> Router {
         .merge(root_router)
         .merge(node::router(ctx.clone()))
         .merge(delegates::router(ctx.clone()))
+        .merge(events::router(ctx.clone()))
         .merge(repos::router(ctx.clone()))
-        .merge(stats::router(ctx));
+        .merge(sessions::router(ctx.clone()))
+        .merge(stats::router(ctx))
+        .layer(middleware::from_fn(axum_extra::conditional_middleware));
 
     Router::new().nest("/v1", routes)
 }
@@ -64,6 +70,16 @@ async fn root_handler(State(ctx): State<Context>) -> impl IntoResponse {
                 "href": "/stats",
                 "rel": "stats",
                 "type": "GET"
+            },
+            {
+                "href": "/events",
+                "rel": "events",
+                "type": "GET"
+            },
+            {
+                "href": "/sessions",
+                "rel": "sessions",
+                "type": "POST"
             }
         ]
     });
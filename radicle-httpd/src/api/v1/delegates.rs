@@ -1,4 +1,4 @@
-use axum::extract::State;
+use axum::extract::{OriginalUri, State};
 use axum::response::IntoResponse;
 use axum::routing::get;
 use axum::{Json, Router};
@@ -6,7 +6,9 @@ use axum::{Json, Router};
 use radicle::identity::Did;
 use radicle::storage::ReadStorage;
 
+use crate::api::auth::Session;
 use crate::api::error::Error;
+use crate::api::pagination::Pagination;
 use crate::api::query::{PaginationQuery, RepoQuery};
 use crate::api::Context;
 use crate::axum_extra::{Path, Query};
@@ -21,16 +23,18 @@ pub fn router(ctx: Context) -> Router {
 /// `GET /delegates/:did/repos`
 async fn delegates_repos_handler(
     State(ctx): State<Context>,
+    session: Option<Session>,
     Path(did): Path<Did>,
+    OriginalUri(uri): OriginalUri,
     Query(qs): Query<PaginationQuery>,
 ) -> impl IntoResponse {
     let PaginationQuery {
         show,
+        cursor,
         page,
         per_page,
     } = qs;
-    let page = page.unwrap_or(0);
-    let per_page = per_page.unwrap_or(10);
+    let pagination = Pagination::new(cursor, page, per_page, 10);
     let storage = &ctx.profile.storage;
     let pinned = &ctx.profile.config.web.pinned;
     let mut repos = match show {
@@ -49,23 +53,19 @@ async fn delegates_repos_handler(
     };
     repos.sort_by_key(|p| p.rid);
 
-    let infos = repos
+    let repos = repos
         .into_iter()
-        .filter_map(|id| {
-            let Ok((repo, doc)) = ctx.repo(id.rid) else {
-                return None;
-            };
-            let Ok(repo_info) = ctx.repo_info(&repo, doc) else {
-                return None;
-            };
+        .filter_map(|info| {
+            let repo = ctx.repo(info.rid, session.as_ref()).ok()?;
 
-            Some(repo_info)
+            Some((info.rid, repo))
         })
-        .skip(page * per_page)
-        .take(per_page)
         .collect::<Vec<_>>();
+    let infos = pagination
+        .sorted(repos)?
+        .filter_map(|(repo, doc)| ctx.repo_info(&repo, doc).ok());
 
-    Ok::<_, Error>(Json(infos))
+    Ok::<_, Error>((infos.link(&uri), Json(infos)))
 }
 
 #[cfg(test)]
@@ -98,80 +98,84 @@ mod routes {
         );
         assert_eq!(
             response.json().await,
-            json!([
-              {
-                "payloads": {
-                  "xyz.radicle.project": {
-                    "data": {
-                      "defaultBranch": "master",
-                      "description": "Rad repository for tests",
-                      "name": "hello-world",
-                    },
-                    "meta": {
-                      "head": HEAD,
-                      "patches": {
-                        "open": 1,
-                        "draft": 0,
-                        "archived": 0,
-                        "merged": 0,
-                      },
-                      "issues": {
-                        "open": 1,
-                        "closed": 0,
+            json!({
+              "items": [
+                {
+                  "payloads": {
+                    "xyz.radicle.project": {
+                      "data": {
+                        "defaultBranch": "master",
+                        "description": "Rad repository for tests",
+                        "name": "hello-world",
                       },
+                      "meta": {
+                        "head": HEAD,
+                        "patches": {
+                          "open": 1,
+                          "draft": 0,
+                          "archived": 0,
+                          "merged": 0,
+                        },
+                        "issues": {
+                          "open": 1,
+                          "closed": 0,
+                        },
+                      }
                     }
-                  }
-                },
-                "delegates": [
-                  {
-                    "id": DID,
-                    "alias": CONTRIBUTOR_ALIAS
-                  }
-                ],
-                "threshold": 1,
-                "visibility": {
-                  "type": "public"
+                  },
+                  "delegates": [
+                    {
+                      "id": DID,
+                      "alias": CONTRIBUTOR_ALIAS
+                    }
+                  ],
+                  "threshold": 1,
+                  "visibility": {
+                    "type": "public"
+                  },
+                  "rid": RID,
+                  "seeding": 1,
                 },
-                "rid": RID,
-                "seeding": 1,
-              },
-              {
-                "payloads": {
-                  "xyz.radicle.project": {
-                    "data": {
-                      "defaultBranch": "master",
-                      "description": "Rad repository for sorting",
-                      "name": "again-hello-world",
-                    },
-                    "meta": {
-                      "head": "344dcd184df5bf37aab6c107fa9371a1c5b3321a",
-                      "patches": {
-                        "open": 0,
-                        "draft": 0,
-                        "archived": 0,
-                        "merged": 0,
-                      },
-                      "issues": {
-                        "open": 0,
-                        "closed": 0,
+                {
+                  "payloads": {
+                    "xyz.radicle.project": {
+                      "data": {
+                        "defaultBranch": "master",
+                        "description": "Rad repository for sorting",
+                        "name": "again-hello-world",
                       },
+                      "meta": {
+                        "head": "344dcd184df5bf37aab6c107fa9371a1c5b3321a",
+                        "patches": {
+                          "open": 0,
+                          "draft": 0,
+                          "archived": 0,
+                          "merged": 0,
+                        },
+                        "issues": {
+                          "open": 0,
+                          "closed": 0,
+                        },
+                      }
                     }
-                  }
-                },
-                "delegates": [
-                  {
-                    "id": DID,
-                    "alias": CONTRIBUTOR_ALIAS
                   },
-                ],
-                "threshold": 1,
-                "visibility": {
-                  "type": "public"
-                },
-                "rid": "rad:z4GypKmh1gkEfmkXtarcYnkvtFUfE",
-                "seeding": 1,
-              }
-            ])
+                  "delegates": [
+                    {
+                      "id": DID,
+                      "alias": CONTRIBUTOR_ALIAS
+                    },
+                  ],
+                  "threshold": 1,
+                  "visibility": {
+                    "type": "public"
+                  },
+                  "rid": "rad:z4GypKmh1gkEfmkXtarcYnkvtFUfE",
+                  "seeding": 1,
+                }
+              ],
+              "total": 2,
+              "nextCursor": null,
+            })
         );
 
         let app = super::router(seed).layer(MockConnectInfo(SocketAddr::from((
@@ -192,80 +196,84 @@ mod routes {
         );
         assert_eq!(
             response.json().await,
-            json!([
-              {
-                "payloads": {
-                  "xyz.radicle.project": {
-                    "data": {
-                      "defaultBranch": "master",
-                      "description": "Rad repository for tests",
-                      "name": "hello-world",
-                    },
-                    "meta": {
-                      "head": HEAD,
-                      "patches": {
-                        "open": 1,
-                        "draft": 0,
-                        "archived": 0,
-                        "merged": 0,
-                      },
-                      "issues": {
-                        "open": 1,
-                        "closed": 0,
+            json!({
+              "items": [
+                {
+                  "payloads": {
+                    "xyz.radicle.project": {
+                      "data": {
+                        "defaultBranch": "master",
+                        "description": "Rad repository for tests",
+                        "name": "hello-world",
                       },
+                      "meta": {
+                        "head": HEAD,
+                        "patches": {
+                          "open": 1,
+                          "draft": 0,
+                          "archived": 0,
+                          "merged": 0,
+                        },
+                        "issues": {
+                          "open": 1,
+                          "closed": 0,
+                        },
+                      }
                     }
-                  }
-                },
-                "delegates": [
-                  {
-                    "id": DID,
-                    "alias": CONTRIBUTOR_ALIAS
-                  }
-                ],
-                "threshold": 1,
-                "visibility": {
-                  "type": "public"
+                  },
+                  "delegates": [
+                    {
+                      "id": DID,
+                      "alias": CONTRIBUTOR_ALIAS
+                    }
+                  ],
+                  "threshold": 1,
+                  "visibility": {
+                    "type": "public"
+                  },
+                  "rid": RID,
+                  "seeding": 1,
                 },
-                "rid": RID,
-                "seeding": 1,
-              },
-              {
-                "payloads": {
-                  "xyz.radicle.project": {
-                    "data": {
-                      "defaultBranch": "master",
-                      "description": "Rad repository for sorting",
-                      "name": "again-hello-world",
-                    },
-                    "meta": {
-                      "head": "344dcd184df5bf37aab6c107fa9371a1c5b3321a",
-                      "patches": {
-                        "open": 0,
-                        "draft": 0,
-                        "archived": 0,
-                        "merged": 0,
-                      },
-                      "issues": {
-                        "open": 0,
-                        "closed": 0,
+                {
+                  "payloads": {
+                    "xyz.radicle.project": {
+                      "data": {
+                        "defaultBranch": "master",
+                        "description": "Rad repository for sorting",
+                        "name": "again-hello-world",
                       },
+                      "meta": {
+                        "head": "344dcd184df5bf37aab6c107fa9371a1c5b3321a",
+                        "patches": {
+                          "open": 0,
+                          "draft": 0,
+                          "archived": 0,
+                          "merged": 0,
+                        },
+                        "issues": {
+                          "open": 0,
+                          "closed": 0,
+                        },
+                      }
                     }
-                  }
-                },
-                "delegates": [
-                  {
-                    "id": DID,
-                    "alias": CONTRIBUTOR_ALIAS
                   },
-                ],
-                "threshold": 1,
-                "visibility": {
-                  "type": "public"
-                },
-                "rid": "rad:z4GypKmh1gkEfmkXtarcYnkvtFUfE",
-                "seeding": 1,
-              }
-            ])
+                  "delegates": [
+                    {
+                      "id": DID,
+                      "alias": CONTRIBUTOR_ALIAS
+                    },
+                  ],
+                  "threshold": 1,
+                  "visibility": {
+                    "type": "public"
+                  },
+                  "rid": "rad:z4GypKmh1gkEfmkXtarcYnkvtFUfE",
+                  "seeding": 1,
+                }
+              ],
+              "total": 2,
+              "nextCursor": null,
+            })
         );
     }
 }
@@ -0,0 +1,170 @@
+use std::convert::Infallible;
+
+use axum::extract::State;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use futures_util::stream;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::api::auth::Session;
+use crate::api::error::Error;
+use crate::api::query::EventsQuery;
+use crate::api::Context;
+use crate::axum_extra::Query;
+
+pub fn router(ctx: Context) -> Router {
+    Router::new()
+        .route("/events", get(events_handler))
+        .with_state(ctx)
+}
+
+/// Stream repository and COB changes as Server-Sent Events.
+/// `GET /events?rid=<rid>`
+///
+/// Only events of repositories visible to the session are streamed.
+async fn events_handler(
+    State(ctx): State<Context>,
+    session: Option<Session>,
+    Query(EventsQuery { rid }): Query<EventsQuery>,
+) -> impl IntoResponse {
+    if let Some(rid) = rid {
+        ctx.repo(rid, session.as_ref())?;
+    }
+    let receiver = ctx.events.subscribe(ctx.profile.clone()).await?;
+    let state = (receiver, ctx, session);
+    let events = stream::unfold(state, move |(mut receiver, ctx, session)| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event)
+                    if rid.is_none_or(|rid| event.rid() == rid)
+                        && ctx.repo(event.rid(), session.as_ref()).is_ok() =>
+                {
+                    let sse = Event::default()
+                        .event(event.name())
+                        .json_data(&event)
+                        .unwrap_or_else(|_| Event::default().event(event.name()));
+
+                    return Some((Ok::<_, Infallible>(sse), (receiver, ctx, session)));
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(n)) => {
+                    tracing::warn!("events: subscriber lagged behind by {n} event(s)");
+                    continue;
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Ok::<_, Error>(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+#[cfg(test)]
+mod routes {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use axum::http::StatusCode;
+    use futures_util::StreamExt as _;
+    use radicle::node::device::Device;
+    use radicle::node::policy::Scope;
+    use radicle::storage::ReadStorage;
+
+    use crate::test::*;
+
+    /// Read events from the stream until one with the given name is found.
+    async fn expect_event(stream: &mut axum::body::BodyDataStream, name: &str) -> String {
+        let mut buffer = String::new();
+
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while let Some(chunk) = stream.next().await {
+                buffer.push_str(&String::from_utf8_lossy(&chunk.unwrap()));
+
+                if let Some(event) = buffer
+                    .split("\n\n")
+                    .find(|e| e.starts_with(&format!("event: {name}\n")))
+                {
+                    return event.to_owned();
+                }
+            }
+            panic!("event stream ended");
+        })
+        .await
+        .unwrap_or_else(|_| panic!("timed out waiting for `{name}` event"))
+    }
+
+    #[tokio::test]
+    async fn test_events() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let rid = radicle::identity::RepoId::from_str(RID).unwrap();
+
+        let response = get(&app, format!("/events?rid={RID}")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let mut stream = response.stream();
+
+        let profile = ctx.profile();
+        let signer = Device::mock_from_seed([0xff; 32]);
+        let repo = profile.storage.repository(rid).unwrap();
+        let mut issues = profile.issues_mut(&repo).unwrap();
+        let issue = issues
+            .create("Issue #2", "Events", &[], &[], [], &signer)
+            .unwrap();
+
+        let event = expect_event(&mut stream, "issueCreated").await;
+        assert!(event.contains(&format!("\"id\":\"{}\"", issue.id())));
+        assert!(event.contains(&format!("\"rid\":\"{RID}\"")));
+
+        profile
+            .policies_mut()
+            .unwrap()
+            .seed(&rid, Scope::Followed)
+            .unwrap();
+
+        let event = expect_event(&mut stream, "seedingPolicyChanged").await;
+        assert!(event.contains("\"scope\":\"followed\""));
+    }
+
+    #[tokio::test]
+    async fn test_events_private_policies() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let token = create_session(&ctx);
+
+        let mut public = get(&app, "/events").await.stream();
+        let mut authorized = get_auth(&app, "/events", token).await.stream();
+
+        let profile = ctx.profile();
+        profile
+            .policies_mut()
+            .unwrap()
+            .unseed(&RID_PRIVATE.parse().unwrap())
+            .unwrap();
+
+        let event = expect_event(&mut authorized, "seedingPolicyChanged").await;
+        assert!(event.contains(&format!("\"rid\":\"{RID_PRIVATE}\"")));
+
+        profile
+            .policies_mut()
+            .unwrap()
+            .seed(&RID.parse().unwrap(), Scope::Followed)
+            .unwrap();
+
+        // The first policy event of an unauthenticated subscriber is the public one.
+        let event = expect_event(&mut public, "seedingPolicyChanged").await;
+        assert!(event.contains(&format!("\"rid\":\"{RID}\"")));
+    }
+
+    #[tokio::test]
+    async fn test_events_private() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(seed(tmp.path()));
+        let response = get(&app, format!("/events?rid={RID_PRIVATE}")).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
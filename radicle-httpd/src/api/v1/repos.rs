@@ -1,29 +1,48 @@
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::collections::{BTreeMap, HashMap};
 
-use axum::extract::{DefaultBodyLimit, State};
+use axum::extract::{DefaultBodyLimit, OriginalUri, State};
 use axum::http::header;
 use axum::response::IntoResponse;
 use axum::routing::get;
 use axum::{Json, Router};
 use hyper::StatusCode;
+use nonempty::NonEmpty;
 use radicle_surf::blob::BlobRef;
 use radicle_surf::{diff, Glob, Oid, Repository};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
 
-use radicle::cob::{issue::cache::Issues as _, patch::cache::Patches as _};
-use radicle::identity::RepoId;
+use radicle::cob;
+use radicle::cob::identity::{self, Identity};
+use radicle::cob::{issue, issue::cache::Issues as _, patch, patch::cache::Patches as _};
+use radicle::cob::{Embed, Label, Uri};
+use radicle::git::canonical::{Canonical, QuorumError};
+use radicle::git::raw::{self as git2, ObjectType, TreeWalkMode, TreeWalkResult};
+use radicle::identity::{Did, RepoId};
+use radicle::node::routing::Store as _;
 use radicle::node::{AliasStore, NodeId};
 use radicle::storage::{ReadRepository, ReadStorage, RemoteRepository};
 
 use crate::api;
+use crate::api::auth::{self, Session};
 use crate::api::error::Error;
-use crate::api::query::{CobsQuery, PaginationQuery, RepoQuery};
-use crate::api::search::{SearchQueryString, SearchResult};
-use crate::api::Context;
-use crate::axum_extra::{cached_response, immutable_response, Path, Query};
+use crate::api::etag::ETag;
+use crate::api::pagination::{CobKey, Pagination};
+use crate::api::query::{
+    CobFields, CobsQuery, PageQuery, PaginationQuery, PatchQuery, RepoQuery, View,
+};
+use crate::api::search::SearchQueryString;
+use crate::api::{Context, README_PATHS};
+use crate::axum_extra::{
+    cached_response, etag_response, immutable_response, revalidated_response, Path, Query,
+};
+use crate::cache::Endpoint;
 
 const MAX_BODY_LIMIT: usize = 4_194_304;
+/// Max number of matching lines returned by a code search.
+const MAX_CODE_SEARCH_MATCHES: usize = 500;
+/// Max number of context lines returned around a code search match.
+const MAX_CODE_SEARCH_CONTEXT: usize = 10;
 
 pub fn router(ctx: Context) -> Router {
     Router::new()
@@ -34,37 +53,63 @@ pub fn router(ctx: Context) -> Router {
         .route("/repos/:rid/commits/:sha", get(commit_handler))
         .route("/repos/:rid/diff/:base/:oid", get(diff_handler))
         .route("/repos/:rid/activity", get(activity_handler))
+        .route("/repos/:rid/identity", get(identity_handler))
         .route("/repos/:rid/tree/:sha/", get(tree_handler_root))
         .route("/repos/:rid/tree/:sha/*path", get(tree_handler))
         .route("/repos/:rid/stats/tree/:sha", get(stats_tree_handler))
         .route("/repos/:rid/remotes", get(remotes_handler))
         .route("/repos/:rid/remotes/:peer", get(remote_handler))
+        .route("/repos/:rid/refs", get(refs_handler))
+        .route("/repos/:rid/tags", get(tags_handler))
+        .route("/repos/:rid/tags/*name", get(tag_handler))
         .route("/repos/:rid/blob/:sha/*path", get(blob_handler))
         .route("/repos/:rid/readme/:sha", get(readme_handler))
-        .route("/repos/:rid/issues", get(issues_handler))
-        .route("/repos/:rid/issues/:id", get(issue_handler))
+        .route("/repos/:rid/blame/:sha/*path", get(blame_handler))
+        .route("/repos/:rid/search/code", get(code_search_handler))
+        .route(
+            "/repos/:rid/issues",
+            get(issues_handler).post(issue_create_handler),
+        )
+        .route(
+            "/repos/:rid/issues/:id",
+            get(issue_handler).patch(issue_update_handler),
+        )
         .route("/repos/:rid/patches", get(patches_handler))
-        .route("/repos/:rid/patches/:id", get(patch_handler))
+        .route(
+            "/repos/:rid/patches/:id",
+            get(patch_handler).patch(patch_update_handler),
+        )
+        .route(
+            "/repos/:rid/patches/:id/revisions/:rev/interdiff/:other",
+            get(interdiff_handler),
+        )
+        .route("/repos/:rid/cobs/:typename", get(cobs_handler))
+        .route("/repos/:rid/cobs/:typename/:id", get(cob_handler))
         .with_state(ctx)
         .layer(DefaultBodyLimit::max(MAX_BODY_LIMIT))
 }
 
 /// List all repos.
 /// `GET /repos`
+///
+/// Private repositories are only listed to the sessions they are visible to.
 async fn repo_root_handler(
     State(ctx): State<Context>,
+    session: Option<Session>,
+    OriginalUri(uri): OriginalUri,
     Query(qs): Query<PaginationQuery>,
 ) -> impl IntoResponse {
     let PaginationQuery {
         show,
+        cursor,
         page,
         per_page,
     } = qs;
-    let page = page.unwrap_or(0);
-    let per_page = per_page.unwrap_or_else(|| match show {
+    let default_per_page = match show {
         RepoQuery::Pinned => ctx.profile.config.web.pinned.repositories.len(),
         _ => 10,
-    });
+    };
+    let pagination = Pagination::new(cursor, page, per_page, default_per_page);
     let storage = &ctx.profile.storage;
     let pinned = &ctx.profile.config.web.pinned;
     let policies = ctx.profile.policies()?;
@@ -73,81 +118,172 @@ async fn repo_root_handler(
         RepoQuery::All => storage
             .repositories()?
             .into_iter()
-            .filter(|repo| repo.doc.visibility().is_public())
+            .filter(|repo| auth::can_view(&repo.doc, session.as_ref()))
             .collect::<Vec<_>>(),
         RepoQuery::Pinned => storage
             .repositories_by_id(pinned.repositories.iter())?
             .into_iter()
-            .filter(|repo| repo.doc.visibility().is_public())
+            .filter(|repo| auth::can_view(&repo.doc, session.as_ref()))
             .collect::<Vec<_>>(),
     };
     repos.sort_by_key(|p| p.rid);
 
-    let infos = repos
+    let repos = repos
         .into_iter()
         .filter_map(|info| {
             if !policies.is_seeding(&info.rid).unwrap_or_default() {
                 return None;
             }
-            let Ok((repo, doc)) = ctx.repo(info.rid) else {
-                return None;
-            };
-            let Ok(repo_info) = ctx.repo_info(&repo, doc) else {
-                return None;
-            };
+            let repo = ctx.repo(info.rid, session.as_ref()).ok()?;
 
-            Some(repo_info)
+            Some((info.rid, repo))
         })
-        .skip(page * per_page)
-        .take(per_page)
         .collect::<Vec<_>>();
+    // Repository infos are only computed for the repositories of the requested page.
+    let infos = pagination
+        .sorted(repos)?
+        .filter_map(|(repo, doc)| ctx.repo_info(&repo, doc).ok());
 
-    Ok::<_, Error>(Json(infos))
+    Ok::<_, Error>((infos.link(&uri), Json(infos)))
 }
 
-/// Search repositories by name.
+/// Search repositories by name, description, README and delegate aliases.
 /// `GET /repos/search?q=<query>`
 ///
-/// We obtain the byte index of the first character of the query that matches the repo name.
-/// And skip if the query doesn't match the repo name.
-///
-/// Sorting algorithm:
-/// If both byte indices are 0, compare by seeding count.
-/// A repo name with a byte index of 0 should come before non-zero indices.
-/// If both indices are non-zero and equal, then compare by seeding count.
-/// If none of the above, all non-zero indices are compared by their seeding count primarily.
+/// The search index is refreshed in the background, which only re-indexes repositories
+/// that changed in storage since the last refresh. Results are ordered by relevance, see
+/// [`crate::api::search::Index::search`].
 async fn repo_search_handler(
     State(ctx): State<Context>,
-    Query(SearchQueryString { q, per_page, page }): Query<SearchQueryString>,
+    OriginalUri(uri): OriginalUri,
+    Query(qs): Query<SearchQueryString>,
 ) -> impl IntoResponse {
+    let SearchQueryString {
+        q,
+        cursor,
+        page,
+        per_page,
+    } = qs;
     let q = q.unwrap_or_default();
-    let page = page.unwrap_or(0);
-    let per_page = per_page.unwrap_or(10);
-    let storage = &ctx.profile.storage;
+    let pagination = Pagination::new(cursor, page, per_page, 10);
+    let index = ctx.search.index(ctx.profile.clone()).await?;
     let aliases = &ctx.profile.aliases();
     let db = &ctx.profile.database()?;
-    let found_repos = storage
-        .repositories()?
-        .into_iter()
-        .filter_map(|info| SearchResult::new(&q, info, db, aliases))
-        .collect::<BTreeSet<SearchResult>>();
 
-    let found_repos = found_repos
+    let found_repos = index
+        .search(&q, db, aliases)
         .into_iter()
-        .skip(page * per_page)
-        .take(per_page)
+        .map(|result| (result.rid, result))
         .collect::<Vec<_>>();
+    let found_repos = pagination.walk(found_repos)?;
 
-    Ok::<_, Error>(cached_response(found_repos, 600).into_response())
+    Ok::<_, Error>((found_repos.link(&uri), cached_response(found_repos, 600)))
 }
 
 /// Get repo metadata.
 /// `GET /repos/:rid`
-async fn repo_handler(State(ctx): State<Context>, Path(rid): Path<RepoId>) -> impl IntoResponse {
-    let (repo, doc) = ctx.repo(rid)?;
-    let info = ctx.repo_info(&repo, doc)?;
+async fn repo_handler(
+    State(ctx): State<Context>,
+    session: Option<Session>,
+    Path(rid): Path<RepoId>,
+) -> impl IntoResponse {
+    let (repo, doc) = ctx.repo(rid, session.as_ref())?;
+    let seeding = ctx.profile.database()?.count(&rid).unwrap_or_default();
+    let info = ctx.repo_info(&repo, doc.clone())?;
+    let etag = ETag::new(rid)
+        .doc(&doc)
+        .sigrefs(&repo)?
+        .value(seeding)
+        .aliases(&info.delegates)
+        .finish();
+
+    Ok::<_, Error>(etag_response(etag, info))
+}
+
+/// Get the revisions of a repo's identity document, oldest first.
+/// `GET /repos/:rid/identity`
+async fn identity_handler(
+    State(ctx): State<Context>,
+    session: Option<Session>,
+    Path(rid): Path<RepoId>,
+    OriginalUri(uri): OriginalUri,
+    Query(qs): Query<PageQuery>,
+) -> impl IntoResponse {
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
+    let identity = Identity::load(&repo)?;
+    let pagination = Pagination::new(qs.cursor, qs.page, qs.per_page, 10);
+    let aliases = ctx.profile.aliases();
+    let revisions = identity.revisions().map(|r| (r.id, r)).collect::<Vec<_>>();
+    let revisions = pagination.walk(revisions)?.try_map(|revision| {
+        let parent = revision.parent.and_then(|p| identity.revision(&p));
+        api::json::cobs::IdentityRevision::new(revision, parent).as_json(&aliases)
+    })?;
+    let etag = ETag::new(rid)
+        .cobs(&repo, &identity::TYPENAME, None)?
+        .aliases(&revisions.items)
+        .finish();
+
+    Ok::<_, Error>((revisions.link(&uri), etag_response(etag, revisions)))
+}
+
+/// List collaborative objects of any type, most recently created first.
+/// `GET /repos/:rid/cobs/:typename`
+async fn cobs_handler(
+    State(ctx): State<Context>,
+    session: Option<Session>,
+    Path((rid, typename)): Path<(RepoId, String)>,
+    OriginalUri(uri): OriginalUri,
+    Query(qs): Query<PageQuery>,
+) -> impl IntoResponse {
+    let typename = parse_typename(&typename)?;
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
+    let pagination = Pagination::new(qs.cursor, qs.page, qs.per_page, 10);
+    let aliases = ctx.profile.aliases();
+    let mut cobs = cob::list::<NonEmpty<cob::Entry>, _>(&repo, &typename)?
+        .into_iter()
+        .map(|cob| {
+            let timestamp = cob.object().first().timestamp as i64;
+            (CobKey::new(*cob.id(), -timestamp), cob)
+        })
+        .collect::<Vec<_>>();
+    cobs.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let cobs = pagination
+        .sorted(cobs)?
+        .map(|cob| api::json::cobs::Cob::new(&cob).as_summary_json(&aliases));
+    let etag = ETag::new(rid)
+        .cobs(&repo, &typename, None)?
+        .aliases(&cobs.items)
+        .finish();
+
+    Ok::<_, Error>((cobs.link(&uri), etag_response(etag, cobs)))
+}
+
+/// Get a collaborative object of any type, with its change history.
+/// `GET /repos/:rid/cobs/:typename/:id`
+async fn cob_handler(
+    State(ctx): State<Context>,
+    session: Option<Session>,
+    Path((rid, typename, id)): Path<(RepoId, String, Oid)>,
+) -> impl IntoResponse {
+    let typename = parse_typename(&typename)?;
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
+    let id = id.into();
+    let cob = cob::get::<NonEmpty<cob::Entry>, _>(&repo, &typename, &id)?.ok_or(Error::NotFound)?;
+    let cob = api::json::cobs::Cob::new(&cob).as_json(&ctx.profile.aliases());
+    let etag = ETag::new(rid)
+        .cobs(&repo, &typename, Some(&id))?
+        .aliases([&cob])
+        .finish();
+
+    Ok::<_, Error>(etag_response(etag, cob))
+}
 
-    Ok::<_, Error>(Json(info))
+#[allow(clippy::result_large_err)]
+fn parse_typename(typename: &str) -> Result<cob::TypeName, Error> {
+    typename
+        .parse()
+        .map_err(|_| Error::BadRequest(format!("invalid type name '{typename}'")))
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -156,23 +292,37 @@ pub struct CommitsQueryString {
     pub parent: Option<String>,
     pub since: Option<i64>,
     pub until: Option<i64>,
+    /// Only list commits that changed this file, or a file under this directory.
+    pub path: Option<String>,
+    /// Keep listing the commits that changed a file before it was renamed.
+    #[serde(default)]
+    pub follow: bool,
+    /// Only list commits with a valid signature, or only commits without one.
+    pub verified: Option<bool>,
+    pub cursor: Option<String>,
     pub page: Option<usize>,
     pub per_page: Option<usize>,
 }
 
 /// Get repo commit range.
-/// `GET /repos/:rid/commits?parent=<sha>`
+/// `GET /repos/:rid/commits?parent=<sha>&path=<path>`
 async fn history_handler(
     State(ctx): State<Context>,
+    session: Option<Session>,
     Path(rid): Path<RepoId>,
+    OriginalUri(uri): OriginalUri,
     Query(qs): Query<CommitsQueryString>,
 ) -> impl IntoResponse {
-    let (repo, _) = ctx.repo(rid)?;
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
     let (_, head) = repo.head()?;
     let CommitsQueryString {
         since,
         until,
         parent,
+        path,
+        follow,
+        verified,
+        cursor,
         page,
         per_page,
     } = qs;
@@ -186,38 +336,141 @@ async fn history_handler(
         Some(commit) => commit,
         None => head.to_string(),
     };
-    let repo = Repository::open(repo.path())?;
+    let surf = Repository::open(repo.path())?;
+    let mut path = path
+        .map(|p| p.trim_matches('/').to_owned())
+        .filter(|p| !p.is_empty());
 
-    // If a pagination is defined, we do not want to paginate the commits, and we return all of them on the first page.
-    let page = page.unwrap_or(0);
-    let per_page = if per_page.is_none() && (since.is_some() || until.is_some()) {
+    // If a time range is defined, we return all the commits in it on the first page, unless
+    // a page size is requested.
+    let default_per_page = if since.is_some() || until.is_some() {
         usize::MAX
     } else {
-        per_page.unwrap_or(30)
+        30
     };
+    let pagination = Pagination::new(cursor, page, per_page, default_per_page);
 
-    let commits = repo
-        .history(&sha)?
-        .filter_map(|commit| {
-            let commit = commit.ok()?;
-            let time = commit.committer.time.seconds();
-            let commit = api::json::commit::Commit::new(&commit).as_json();
-            match (since, until) {
-                (Some(since), Some(until)) if time >= since && time < until => Some(commit),
-                (Some(since), None) if time >= since => Some(commit),
-                (None, Some(until)) if time < until => Some(commit),
-                (None, None) => Some(commit),
-                _ => None,
+    // Counting the matching commits means diffing, or verifying, every commit in the history.
+    let count = path.is_none() && verified.is_none();
+    let mut matches = |commit: &radicle_surf::Commit| -> Result<bool, Error> {
+        let time = commit.committer.time.seconds();
+        let in_range = match (since, until) {
+            (Some(since), Some(until)) => time >= since && time < until,
+            (Some(since), None) => time >= since,
+            (None, Some(until)) => time < until,
+            (None, None) => true,
+        };
+        // When following renames, the path is tracked through the whole history, so that
+        // commits in the time range are matched against the name the file had at the time.
+        if !in_range && !follow {
+            return Ok(false);
+        }
+        if let Some(p) = &mut path {
+            if !changes_path(&repo.backend, commit.id.into(), p, follow)? {
+                return Ok(false);
             }
-        })
-        .skip(page * per_page)
-        .take(per_page)
-        .collect::<Vec<_>>();
+        }
+        if !in_range {
+            return Ok(false);
+        }
+        if let Some(verified) = verified {
+            let signature = api::signature::Signature::of_commit(&repo.backend, commit.id.into())?;
+            let valid = signature.is_some_and(|s| s.status == api::signature::Status::Valid);
+            if valid != verified {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    };
+    let commits = surf.history(&sha)?.filter_map(|commit| {
+        let commit = match commit {
+            Ok(commit) => commit,
+            Err(err) => return Some(Err(Error::from(err))),
+        };
+        match matches(&commit) {
+            Ok(true) => Some(Ok((commit.id, commit))),
+            Ok(false) => None,
+            Err(err) => Some(Err(err)),
+        }
+    });
+    let aliases = ctx.profile.aliases();
+    let mut signed = false;
+    let commits = pagination.stream(commits, count)?.try_map(|commit| {
+        let mut json = api::json::commit::Commit::new(&commit)
+            .signed(&repo.backend)?
+            .as_json();
+        signed |= api::json::commit::Commit::with_signer(&mut json, &aliases);
 
-    if is_immutable {
-        Ok::<_, Error>(immutable_response(commits).into_response())
+        Ok::<_, Error>(json)
+    })?;
+    let link = commits.link(&uri);
+
+    // The aliases of signers can change, so these responses can't be treated as immutable.
+    if is_immutable && signed {
+        Ok::<_, Error>((link, revalidated_response(commits)).into_response())
+    } else if is_immutable {
+        Ok::<_, Error>((link, immutable_response(commits)).into_response())
+    } else {
+        Ok::<_, Error>((link, Json(commits)).into_response())
+    }
+}
+
+/// Whether a commit changed a file or directory, compared to all its parents. When following
+/// renames and the commit renamed the file, the path is set to the previous name of the file.
+fn changes_path(
+    repo: &git2::Repository,
+    oid: git2::Oid,
+    path: &mut String,
+    follow: bool,
+) -> Result<bool, git2::Error> {
+    let commit = repo.find_commit(oid)?;
+    let tree = commit.tree()?;
+    let parents = commit
+        .parents()
+        .map(|p| p.tree())
+        .collect::<Result<Vec<_>, _>>()?;
+    let entry = |tree: &git2::Tree, path: &str| {
+        tree.get_path(std::path::Path::new(path))
+            .ok()
+            .map(|e| e.id())
+    };
+    let id = entry(&tree, path);
+
+    if parents.iter().any(|p| entry(p, path) == id) {
+        return Ok(false);
+    }
+    if id.is_none() {
+        // Deleted by this commit.
+        return Ok(!parents.is_empty());
+    }
+    // Added by this commit, possibly by renaming another file.
+    if follow && parents.iter().all(|p| entry(p, path).is_none()) {
+        let mut diff = repo.diff_tree_to_tree(parents.first(), Some(&tree), None)?;
+        diff.find_similar(None)?;
+
+        let from = diff
+            .deltas()
+            .filter(|d| d.status() == git2::Delta::Renamed)
+            .find(|d| d.new_file().path() == Some(std::path::Path::new(path)))
+            .and_then(|d| d.old_file().path()?.to_str().map(ToOwned::to_owned));
+        if let Some(from) = from {
+            *path = from;
+        }
+    }
+    Ok(true)
+}
+
+/// Respond with the JSON of immutable objects, eg. commits, after adding the aliases of the
+/// keys that signed them with `signers`, which returns whether there were any. Since aliases
+/// can change, responses with signers are revalidated, rather than treated as immutable.
+fn signed_response(
+    mut response: Value,
+    signers: impl FnOnce(&mut Value) -> bool,
+) -> axum::response::Response {
+    if signers(&mut response) {
+        revalidated_response(response)
     } else {
-        Ok::<_, Error>(Json(commits).into_response())
+        immutable_response(response)
     }
 }
 
@@ -225,142 +478,176 @@ async fn history_handler(
 /// `GET /repos/:rid/commits/:sha`
 async fn commit_handler(
     State(ctx): State<Context>,
+    session: Option<Session>,
     Path((rid, sha)): Path<(RepoId, Oid)>,
 ) -> impl IntoResponse {
-    let (repo, _) = ctx.repo(rid)?;
-    let repo = Repository::open(repo.path())?;
-    let commit = repo.commit(sha)?;
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
+    let response = ctx.cached(Endpoint::Commit, rid, sha, || {
+        let git = &repo.backend;
+        let repo = Repository::open(repo.path())?;
+        let commit = repo.commit(sha)?;
 
-    let diff = repo.diff_commit(commit.id)?;
-    let glob = Glob::all_heads().branches().and(Glob::all_remotes());
-    let branches: Vec<String> = repo
-        .revision_branches(commit.id, glob)?
-        .iter()
-        .map(|b| b.refname().to_string())
-        .collect();
-
-    let mut files: HashMap<Oid, BlobRef<'_>> = HashMap::new();
-    diff.files().for_each(|file_diff| match file_diff {
-        diff::FileDiff::Added(added) => {
-            if let Ok(blob) = repo.blob_ref(added.new.oid) {
-                files.insert(blob.id(), blob);
+        let diff = repo.diff_commit(commit.id)?;
+        let glob = Glob::all_heads().branches().and(Glob::all_remotes());
+        let branches: Vec<String> = repo
+            .revision_branches(commit.id, glob)?
+            .iter()
+            .map(|b| b.refname().to_string())
+            .collect();
+
+        let mut files: HashMap<Oid, BlobRef<'_>> = HashMap::new();
+        diff.files().for_each(|file_diff| match file_diff {
+            diff::FileDiff::Added(added) => {
+                if let Ok(blob) = repo.blob_ref(added.new.oid) {
+                    files.insert(blob.id(), blob);
+                }
             }
-        }
-        diff::FileDiff::Deleted(deleted) => {
-            if let Ok(old_blob) = repo.blob_ref(deleted.old.oid) {
-                files.insert(old_blob.id(), old_blob);
+            diff::FileDiff::Deleted(deleted) => {
+                if let Ok(old_blob) = repo.blob_ref(deleted.old.oid) {
+                    files.insert(old_blob.id(), old_blob);
+                }
             }
-        }
-        diff::FileDiff::Modified(modified) => {
-            if let (Ok(old_blob), Ok(new_blob)) = (
-                repo.blob_ref(modified.old.oid),
-                repo.blob_ref(modified.new.oid),
-            ) {
-                files.insert(old_blob.id(), old_blob);
-                files.insert(new_blob.id(), new_blob);
+            diff::FileDiff::Modified(modified) => {
+                if let (Ok(old_blob), Ok(new_blob)) = (
+                    repo.blob_ref(modified.old.oid),
+                    repo.blob_ref(modified.new.oid),
+                ) {
+                    files.insert(old_blob.id(), old_blob);
+                    files.insert(new_blob.id(), new_blob);
+                }
             }
-        }
-        diff::FileDiff::Moved(moved) => {
-            if let (Ok(old_blob), Ok(new_blob)) =
-                (repo.blob_ref(moved.old.oid), repo.blob_ref(moved.new.oid))
-            {
-                files.insert(old_blob.id(), old_blob);
-                files.insert(new_blob.id(), new_blob);
+            diff::FileDiff::Moved(moved) => {
+                if let (Ok(old_blob), Ok(new_blob)) =
+                    (repo.blob_ref(moved.old.oid), repo.blob_ref(moved.new.oid))
+                {
+                    files.insert(old_blob.id(), old_blob);
+                    files.insert(new_blob.id(), new_blob);
+                }
             }
-        }
-        diff::FileDiff::Copied(copied) => {
-            if let (Ok(old_blob), Ok(new_blob)) =
-                (repo.blob_ref(copied.old.oid), repo.blob_ref(copied.new.oid))
-            {
-                files.insert(old_blob.id(), old_blob);
-                files.insert(new_blob.id(), new_blob);
+            diff::FileDiff::Copied(copied) => {
+                if let (Ok(old_blob), Ok(new_blob)) =
+                    (repo.blob_ref(copied.old.oid), repo.blob_ref(copied.new.oid))
+                {
+                    files.insert(old_blob.id(), old_blob);
+                    files.insert(new_blob.id(), new_blob);
+                }
             }
-        }
-    });
+        });
 
-    let response: serde_json::Value = json!({
-      "commit": api::json::commit::Commit::new(&commit).as_json(),
-      "diff": api::json::diff::Diff::new(&diff).as_json(),
-      "files": files,
-      "branches": branches
-    });
-    Ok::<_, Error>(immutable_response(response))
+        let response: serde_json::Value = json!({
+          "commit": api::json::commit::Commit::new(&commit).signed(git)?.as_json(),
+          "diff": api::json::diff::Diff::new(&diff).as_json(),
+          "files": files,
+          "branches": branches
+        });
+
+        Ok(response)
+    })?;
+
+    Ok::<_, Error>(signed_response(response, |r| {
+        api::json::commit::Commit::with_signer(&mut r["commit"], &ctx.profile.aliases())
+    }))
+}
+
+/// Add the aliases of signers to the JSON of the given commits. Returns whether any commit has
+/// a signer.
+fn with_signers<'a>(
+    commits: impl IntoIterator<Item = &'a mut Value>,
+    aliases: &impl AliasStore,
+) -> bool {
+    commits.into_iter().fold(false, |signed, commit| {
+        api::json::commit::Commit::with_signer(commit, aliases) || signed
+    })
 }
 
 /// Get diff between two commits
 /// `GET /repos/:rid/diff/:base/:oid`
 async fn diff_handler(
     State(ctx): State<Context>,
+    session: Option<Session>,
     Path((rid, base, oid)): Path<(RepoId, Oid, Oid)>,
 ) -> impl IntoResponse {
-    let (repo, _) = ctx.repo(rid)?;
-    let repo = Repository::open(repo.path())?;
-    let base = repo.commit(base)?;
-    let commit = repo.commit(oid)?;
-    let diff = repo.diff(base.id, commit.id)?;
-    let mut files: HashMap<Oid, BlobRef<'_>> = HashMap::new();
-    diff.files().for_each(|file_diff| match file_diff {
-        diff::FileDiff::Added(added) => {
-            if let Ok(new_blob) = repo.blob_ref(added.new.oid) {
-                files.insert(new_blob.id(), new_blob);
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
+    let response = ctx.cached(Endpoint::Diff, rid, format!("{base}..{oid}"), || {
+        let git = &repo.backend;
+        let repo = Repository::open(repo.path())?;
+        let base = repo.commit(base)?;
+        let commit = repo.commit(oid)?;
+        let diff = repo.diff(base.id, commit.id)?;
+        let mut files: HashMap<Oid, BlobRef<'_>> = HashMap::new();
+        diff.files().for_each(|file_diff| match file_diff {
+            diff::FileDiff::Added(added) => {
+                if let Ok(new_blob) = repo.blob_ref(added.new.oid) {
+                    files.insert(new_blob.id(), new_blob);
+                }
             }
-        }
-        diff::FileDiff::Deleted(deleted) => {
-            if let Ok(old_blob) = repo.blob_ref(deleted.old.oid) {
-                files.insert(old_blob.id(), old_blob);
+            diff::FileDiff::Deleted(deleted) => {
+                if let Ok(old_blob) = repo.blob_ref(deleted.old.oid) {
+                    files.insert(old_blob.id(), old_blob);
+                }
             }
-        }
-        diff::FileDiff::Modified(modified) => {
-            if let (Ok(new_blob), Ok(old_blob)) = (
-                repo.blob_ref(modified.old.oid),
-                repo.blob_ref(modified.new.oid),
-            ) {
-                files.insert(new_blob.id(), new_blob);
-                files.insert(old_blob.id(), old_blob);
+            diff::FileDiff::Modified(modified) => {
+                if let (Ok(new_blob), Ok(old_blob)) = (
+                    repo.blob_ref(modified.old.oid),
+                    repo.blob_ref(modified.new.oid),
+                ) {
+                    files.insert(new_blob.id(), new_blob);
+                    files.insert(old_blob.id(), old_blob);
+                }
             }
-        }
-        diff::FileDiff::Moved(moved) => {
-            if let (Ok(new_blob), Ok(old_blob)) =
-                (repo.blob_ref(moved.new.oid), repo.blob_ref(moved.old.oid))
-            {
-                files.insert(new_blob.id(), new_blob);
-                files.insert(old_blob.id(), old_blob);
+            diff::FileDiff::Moved(moved) => {
+                if let (Ok(new_blob), Ok(old_blob)) =
+                    (repo.blob_ref(moved.new.oid), repo.blob_ref(moved.old.oid))
+                {
+                    files.insert(new_blob.id(), new_blob);
+                    files.insert(old_blob.id(), old_blob);
+                }
             }
-        }
-        diff::FileDiff::Copied(copied) => {
-            if let (Ok(new_blob), Ok(old_blob)) =
-                (repo.blob_ref(copied.new.oid), repo.blob_ref(copied.old.oid))
-            {
-                files.insert(new_blob.id(), new_blob);
-                files.insert(old_blob.id(), old_blob);
+            diff::FileDiff::Copied(copied) => {
+                if let (Ok(new_blob), Ok(old_blob)) =
+                    (repo.blob_ref(copied.new.oid), repo.blob_ref(copied.old.oid))
+                {
+                    files.insert(new_blob.id(), new_blob);
+                    files.insert(old_blob.id(), old_blob);
+                }
             }
-        }
-    });
+        });
 
-    let commits = repo
-        .history(commit.id)?
-        .take_while(|c| {
-            if let Ok(c) = c {
-                c.id != base.id
-            } else {
-                false
-            }
-        })
-        .map(|r| r.map(|c| api::json::commit::Commit::new(&c).as_json()))
-        .collect::<Result<Vec<_>, _>>()?;
+        let commits = repo
+            .history(commit.id)?
+            .take_while(|c| {
+                if let Ok(c) = c {
+                    c.id != base.id
+                } else {
+                    false
+                }
+            })
+            .map(|r| {
+                let commit = r?;
+                let commit = api::json::commit::Commit::new(&commit).signed(git)?;
+                Ok::<_, Error>(commit.as_json())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
-    let response = json!({ "diff": diff, "files": files, "commits": commits });
+        let response = json!({ "diff": diff, "files": files, "commits": commits });
 
-    Ok::<_, Error>(immutable_response(response))
+        Ok(response)
+    })?;
+
+    Ok::<_, Error>(signed_response(response, |r| {
+        let commits = r["commits"].as_array_mut().into_iter().flatten();
+        with_signers(commits, &ctx.profile.aliases())
+    }))
 }
 
 /// Get repo activity for the past year.
 /// `GET /repos/:rid/activity`
 async fn activity_handler(
     State(ctx): State<Context>,
+    session: Option<Session>,
     Path(rid): Path<RepoId>,
 ) -> impl IntoResponse {
-    let (repo, _) = ctx.repo(rid)?;
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
     let current_date = chrono::Utc::now().timestamp();
     // SAFETY: The number of weeks is static and not out of bounds.
     #[allow(clippy::unwrap_used)]
@@ -387,55 +674,61 @@ async fn activity_handler(
 /// `GET /repos/:rid/tree/:sha/`
 async fn tree_handler_root(
     State(ctx): State<Context>,
+    session: Option<Session>,
     Path((rid, sha)): Path<(RepoId, Oid)>,
 ) -> impl IntoResponse {
-    tree_handler(State(ctx), Path((rid, sha, String::new()))).await
+    tree_handler(State(ctx), session, Path((rid, sha, String::new()))).await
 }
 
 /// Get repo source tree.
 /// `GET /repos/:rid/tree/:sha/*path`
 async fn tree_handler(
     State(ctx): State<Context>,
+    session: Option<Session>,
     Path((rid, sha, path)): Path<(RepoId, Oid, String)>,
 ) -> impl IntoResponse {
-    let (repo, _) = ctx.repo(rid)?;
-
-    if let Some(ref cache) = ctx.cache {
-        let cache = &mut cache.tree.lock().await;
-        if let Some(response) = cache.get(&(rid, sha, path.clone())) {
-            return Ok::<_, Error>(immutable_response(response.clone()));
-        }
-    }
-
-    let repo = Repository::open(repo.path())?;
-    let tree = repo.tree(sha, &path)?;
-    let response = api::json::commit::Tree::new(&tree).as_json(&path);
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
+    let response = ctx.cached(Endpoint::Tree, rid, format!("{sha}/{path}"), || {
+        let git = &repo.backend;
+        let repo = Repository::open(repo.path())?;
+        let tree = repo.tree(sha, &path)?;
 
-    if let Some(cache) = &ctx.cache {
-        let cache = &mut cache.tree.lock().await;
-        cache.put((rid, sha, path.clone()), response.clone());
-    }
+        Ok(api::json::commit::Tree::new(&tree)
+            .signed(git)?
+            .as_json(&path))
+    })?;
 
-    Ok::<_, Error>(immutable_response(response))
+    Ok::<_, Error>(signed_response(response, |r| {
+        api::json::commit::Commit::with_signer(&mut r["lastCommit"], &ctx.profile.aliases())
+    }))
 }
 
 /// Get repo source tree stats.
 /// `GET /repos/:rid/stats/tree/:sha`
 async fn stats_tree_handler(
     State(ctx): State<Context>,
+    session: Option<Session>,
     Path((rid, sha)): Path<(RepoId, Oid)>,
 ) -> impl IntoResponse {
-    let (repo, _) = ctx.repo(rid)?;
-    let repo = Repository::open(repo.path())?;
-    let stats = repo.stats_from(&sha)?;
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
+    let stats = ctx.cached(Endpoint::TreeStats, rid, sha, || {
+        let repo = Repository::open(repo.path())?;
+        let stats = repo.stats_from(&sha)?;
+
+        Ok(json!(stats))
+    })?;
 
     Ok::<_, Error>(immutable_response(stats))
 }
 
 /// Get all repo remotes.
 /// `GET /repos/:rid/remotes`
-async fn remotes_handler(State(ctx): State<Context>, Path(rid): Path<RepoId>) -> impl IntoResponse {
-    let (repo, doc) = ctx.repo(rid)?;
+async fn remotes_handler(
+    State(ctx): State<Context>,
+    session: Option<Session>,
+    Path(rid): Path<RepoId>,
+) -> impl IntoResponse {
+    let (repo, doc) = ctx.repo(rid, session.as_ref())?;
     let delegates = doc.delegates();
     let aliases = &ctx.profile.aliases();
     let remotes = repo
@@ -467,17 +760,23 @@ async fn remotes_handler(State(ctx): State<Context>, Path(rid): Path<RepoId>) ->
             }
         })
         .collect::<Vec<_>>();
+    let etag = ETag::new(rid)
+        .doc(&doc)
+        .sigrefs(&repo)?
+        .aliases(&remotes)
+        .finish();
 
-    Ok::<_, Error>(Json(remotes))
+    Ok::<_, Error>(etag_response(etag, remotes))
 }
 
 /// Get repo remote.
 /// `GET /repos/:rid/remotes/:peer`
 async fn remote_handler(
     State(ctx): State<Context>,
+    session: Option<Session>,
     Path((rid, node_id)): Path<(RepoId, NodeId)>,
 ) -> impl IntoResponse {
-    let (repo, doc) = ctx.repo(rid)?;
+    let (repo, doc) = ctx.repo(rid, session.as_ref())?;
     let delegates = doc.delegates();
     let remote = repo.remote(&node_id)?;
     let refs = remote
@@ -494,19 +793,311 @@ async fn remote_handler(
         "heads": refs,
         "delegate": delegates.contains(&remote.id.into()),
     });
+    let etag = ETag::new(rid)
+        .doc(&doc)
+        .sigrefs_of(&repo, &node_id)?
+        .finish();
+
+    Ok::<_, Error>(etag_response(etag, remote))
+}
+
+/// Get the canonical branches of a repo, computed from the delegates' branches and the
+/// threshold of the identity document. For each branch, delegates are compared to the
+/// canonical tip: they either agree with it, are ahead of it, behind it, or diverge from it.
+/// `GET /repos/:rid/refs`
+async fn refs_handler(
+    State(ctx): State<Context>,
+    session: Option<Session>,
+    Path(rid): Path<RepoId>,
+) -> impl IntoResponse {
+    let (repo, doc) = ctx.repo(rid, session.as_ref())?;
+    let delegates = doc.delegates().as_ref();
+    let threshold = doc.threshold();
+    let default_branch = doc.project().ok().map(|p| p.default_branch().to_string());
+    let aliases = ctx.profile.aliases();
+    let git = &repo.backend;
+
+    let mut names = std::collections::BTreeSet::new();
+    for delegate in delegates.iter() {
+        let prefix = format!("refs/namespaces/{}/refs/heads/", delegate.as_key());
+        for r in git.references_glob(&format!("{prefix}*"))? {
+            if let Some(name) = r?.name().and_then(|n| n.strip_prefix(&prefix)) {
+                names.insert(name.to_owned());
+            }
+        }
+    }
+
+    let mut branches = Vec::with_capacity(names.len());
+    for name in names {
+        let Ok(branch) = radicle::git::RefString::try_from(name.as_str()) else {
+            continue;
+        };
+        let qualified = radicle::git::Qualified::from(radicle::git::lit::refs_heads(&branch));
+        let canonical = Canonical::reference(&repo, delegates, &qualified)?;
+        let (tip, quorum) = match canonical.quorum(threshold, git) {
+            Ok(tip) => (Some(tip), "reached"),
+            Err(QuorumError::Diverging(_)) => (None, "diverging"),
+            Err(QuorumError::NoCandidates(_)) => (None, "notMet"),
+            Err(QuorumError::Git(e)) => return Err(e.into()),
+        };
+        let tips = canonical.tips().collect::<BTreeMap<_, _>>();
+        let delegates = delegates
+            .iter()
+            .map(|did| {
+                let mut json = api::json::Author::new(did).as_json(&aliases);
+                let head = tips.get(did).copied();
+                let (status, ahead, behind) = match (head, tip) {
+                    (None, _) => ("missing", None, None),
+                    (Some(head), Some(tip)) => {
+                        let (ahead, behind) = git.graph_ahead_behind(**head, *tip)?;
+                        let status = match (ahead, behind) {
+                            (0, 0) => "agrees",
+                            (_, 0) => "ahead",
+                            (0, _) => "behind",
+                            _ => "diverges",
+                        };
+                        (status, Some(ahead), Some(behind))
+                    }
+                    (Some(_), None) => ("unknown", None, None),
+                };
+                json["head"] = json!(head);
+                json["status"] = json!(status);
+                json["ahead"] = json!(ahead);
+                json["behind"] = json!(behind);
+
+                Ok::<_, Error>(json)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        branches.push(json!({
+            "name": name,
+            "default": default_branch.as_deref() == Some(name.as_str()),
+            "quorum": quorum,
+            "canonical": tip,
+            "delegates": delegates,
+        }));
+    }
+    let etag = ETag::new(rid)
+        .doc(&doc)
+        .sigrefs(&repo)?
+        .aliases(&branches)
+        .finish();
+
+    Ok::<_, Error>(etag_response(
+        etag,
+        json!({ "threshold": threshold, "branches": branches }),
+    ))
+}
+
+/// Get repo tags, canonical or published by remotes.
+/// `GET /repos/:rid/tags`
+async fn tags_handler(
+    State(ctx): State<Context>,
+    session: Option<Session>,
+    Path(rid): Path<RepoId>,
+) -> impl IntoResponse {
+    let (repo, doc) = ctx.repo(rid, session.as_ref())?;
+    let tags = tags(&ctx, rid, &repo, &doc, None)?;
+    let etag = ETag::new(rid)
+        .doc(&doc)
+        .sigrefs(&repo)?
+        .tags(&repo)?
+        .aliases(&tags)
+        .finish();
+
+    Ok::<_, Error>(etag_response(etag, tags))
+}
+
+/// Get the tags with a given name. Remotes may disagree on the object a tag name points to,
+/// in which case there is one tag per object.
+/// `GET /repos/:rid/tags/*name`
+async fn tag_handler(
+    State(ctx): State<Context>,
+    session: Option<Session>,
+    Path((rid, name)): Path<(RepoId, String)>,
+) -> impl IntoResponse {
+    let (repo, doc) = ctx.repo(rid, session.as_ref())?;
+    let tags = tags(&ctx, rid, &repo, &doc, Some(&name))?;
+    if tags.is_empty() {
+        return Err(Error::NotFound);
+    }
+    let etag = ETag::new(rid)
+        .doc(&doc)
+        .sigrefs(&repo)?
+        .tags(&repo)?
+        .aliases(&tags)
+        .finish();
+
+    Ok::<_, Error>(etag_response(etag, tags))
+}
+
+/// List the tags of a repo by name, one per name and object. Canonical tags come first, and
+/// each tag lists the remotes publishing it.
+#[allow(clippy::result_large_err)]
+fn tags(
+    ctx: &Context,
+    rid: RepoId,
+    repo: &radicle::storage::git::Repository,
+    doc: &radicle::identity::DocAt,
+    name: Option<&str>,
+) -> Result<Vec<Value>, Error> {
+    let delegates = doc.delegates();
+    let aliases = ctx.profile.aliases();
+    let git = &repo.backend;
+
+    // Tags by name and object, with whether they are canonical and the remotes publishing them.
+    let mut tags = BTreeMap::<(String, git2::Oid), (bool, Vec<NodeId>)>::new();
+    for r in git.references_glob("refs/tags/*")? {
+        let r = r?;
+        if let (Some(tag), Some(oid)) = (
+            r.name().and_then(|n| n.strip_prefix("refs/tags/")),
+            r.target(),
+        ) {
+            tags.entry((tag.to_owned(), oid)).or_default().0 = true;
+        }
+    }
+    for r in git.references_glob("refs/namespaces/*/refs/tags/*")? {
+        let r = r?;
+        let Some((remote, tag)) = r
+            .name()
+            .and_then(|n| n.strip_prefix("refs/namespaces/"))
+            .and_then(|n| n.split_once("/refs/tags/"))
+        else {
+            continue;
+        };
+        if let (Ok(remote), Some(oid)) = (remote.parse::<NodeId>(), r.target()) {
+            tags.entry((tag.to_owned(), oid))
+                .or_default()
+                .1
+                .push(remote);
+        }
+    }
+    let mut tags = tags
+        .into_iter()
+        .filter(|((tag, _), _)| name.is_none_or(|name| name == tag))
+        .collect::<Vec<_>>();
+    tags.sort_by(|((a, _), (a_canonical, _)), ((b, _), (b_canonical, _))| {
+        a.cmp(b).then(b_canonical.cmp(a_canonical))
+    });
 
-    Ok::<_, Error>(Json(remote))
+    tags.into_iter()
+        .map(|((tag, oid), (canonical, remotes))| {
+            let object = git.find_object(oid, None)?;
+            let target = object.peel_to_commit()?.id();
+            let refname = match (canonical, remotes.first()) {
+                (false, Some(remote)) => format!("refs/namespaces/{remote}/refs/tags/{tag}"),
+                _ => format!("refs/tags/{tag}"),
+            };
+            let remotes = remotes
+                .iter()
+                .map(|remote| {
+                    let mut json = api::json::Author::new(&(*remote).into()).as_json(&aliases);
+                    json["delegate"] = delegates.contains(&(*remote).into()).into();
+                    json
+                })
+                .collect::<Vec<_>>();
+            let mut json = json!({
+                "name": tag,
+                "oid": oid.to_string(),
+                "target": target.to_string(),
+                "canonical": canonical,
+                "remotes": remotes,
+                "tagger": null,
+                "message": null,
+                "signature": null,
+                "archive": format!("/raw/{rid}/archive/{refname}"),
+            });
+            if let Some(annotated) = object.as_tag() {
+                json["tagger"] = annotated
+                    .tagger()
+                    .map(|t| {
+                        json!({
+                            "name": t.name(),
+                            "email": t.email(),
+                            "time": t.when().seconds(),
+                        })
+                    })
+                    .into();
+                json["message"] = annotated.message().map(api::signature::tag_message).into();
+                json["signature"] = api::signature::Signature::of_tag(git, oid)?
+                    .map(|s| {
+                        let mut json = api::json::commit::Signature::new(&s).as_json();
+                        api::json::commit::Signature::with_signer(&mut json, &aliases);
+                        json
+                    })
+                    .into();
+            }
+            Ok(json)
+        })
+        .collect()
 }
 
 /// Get repo source file.
 /// `GET /repos/:rid/blob/:sha/*path`
 async fn blob_handler(
     State(ctx): State<Context>,
+    session: Option<Session>,
     Path((rid, sha, path)): Path<(RepoId, Oid, String)>,
 ) -> impl IntoResponse {
-    let (repo, _) = ctx.repo(rid)?;
-    let repo = Repository::open(repo.path())?;
-    let blob = repo.blob(sha, &path)?;
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
+    let aliases = ctx.profile.aliases();
+    let signers = |blob: &mut Value| {
+        api::json::commit::Commit::with_signer(&mut blob["lastCommit"], &aliases)
+    };
+    let id = format!("{sha}/{path}");
+    if let Some(blob) = ctx
+        .cache
+        .as_ref()
+        .and_then(|c| c.get(Endpoint::Blob, rid, &id))
+    {
+        return Ok::<_, Error>(signed_response(blob, signers));
+    }
+    let surf = Repository::open(repo.path())?;
+    let blob = surf.blob(sha, &path)?;
+
+    if blob.size() > MAX_BODY_LIMIT {
+        return Ok::<_, Error>(
+            (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                [(header::CACHE_CONTROL, "no-cache")],
+                Json(json!([])),
+            )
+                .into_response(),
+        );
+    }
+    let blob = api::json::commit::Blob::new(&blob)
+        .signed(&repo.backend)?
+        .as_json(&path);
+    if let Some(cache) = &ctx.cache {
+        cache.put(Endpoint::Blob, rid, id, &blob);
+    }
+    Ok::<_, Error>(signed_response(blob, signers))
+}
+
+/// Get the commits that last changed each line of a source file, as line ranges grouped
+/// by commit, in the order the commits first appear in the file.
+/// `GET /repos/:rid/blame/:sha/*path`
+async fn blame_handler(
+    State(ctx): State<Context>,
+    session: Option<Session>,
+    Path((rid, sha, path)): Path<(RepoId, Oid, String)>,
+) -> impl IntoResponse {
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
+    let id = format!("{sha}/{path}");
+    let aliases = ctx.profile.aliases();
+    let signers = |blame: &mut Value| {
+        let groups = blame["blame"].as_array_mut().into_iter().flatten();
+        with_signers(groups.map(|group| &mut group["commit"]), &aliases)
+    };
+    if let Some(blame) = ctx
+        .cache
+        .as_ref()
+        .and_then(|c| c.get(Endpoint::Blame, rid, &id))
+    {
+        return Ok::<_, Error>(signed_response(blame, signers));
+    }
+    let surf = Repository::open(repo.path())?;
+    let blob = surf.blob(sha, &path)?;
 
     if blob.size() > MAX_BODY_LIMIT {
         return Ok::<_, Error>(
@@ -518,35 +1109,70 @@ async fn blob_handler(
                 .into_response(),
         );
     }
-    Ok::<_, Error>(
-        immutable_response(api::json::commit::Blob::new(&blob).as_json(&path)).into_response(),
-    )
+    let mut opts = git2::BlameOptions::new();
+    opts.newest_commit(sha.into());
+    let blame = repo
+        .backend
+        .blame_file(std::path::Path::new(&path), Some(&mut opts))?;
+
+    let mut groups: Vec<(Oid, Vec<Value>)> = Vec::new();
+    for hunk in blame.iter() {
+        let commit = Oid::from(hunk.final_commit_id());
+        let start = hunk.final_start_line();
+        let range = json!({ "start": start, "end": start + hunk.lines_in_hunk() - 1 });
+
+        match groups.iter_mut().find(|(oid, _)| *oid == commit) {
+            Some((_, ranges)) => ranges.push(range),
+            None => groups.push((commit, vec![range])),
+        }
+    }
+    let groups = groups
+        .into_iter()
+        .map(|(oid, ranges)| {
+            let commit = surf.commit(oid)?;
+            Ok::<_, Error>(json!({
+                "commit": api::json::commit::Commit::new(&commit)
+                    .signed(&repo.backend)?
+                    .as_json(),
+                "ranges": ranges,
+            }))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let blame = json!({ "path": path, "blame": groups });
+
+    if let Some(cache) = &ctx.cache {
+        cache.put(Endpoint::Blame, rid, id, &blame);
+    }
+    Ok::<_, Error>(signed_response(blame, signers))
 }
 
 /// Get repo readme.
 /// `GET /repos/:rid/readme/:sha`
 async fn readme_handler(
     State(ctx): State<Context>,
+    session: Option<Session>,
     Path((rid, sha)): Path<(RepoId, Oid)>,
 ) -> impl IntoResponse {
-    let (repo, _) = ctx.repo(rid)?;
-    let repo = Repository::open(repo.path())?;
-    let paths = [
-        "README",
-        "README.md",
-        "README.markdown",
-        "README.txt",
-        "README.rst",
-        "README.org",
-        "Readme.md",
-    ];
-
-    for path in paths
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
+    let aliases = ctx.profile.aliases();
+    let signers = |readme: &mut Value| {
+        api::json::commit::Commit::with_signer(&mut readme["lastCommit"], &aliases)
+    };
+    if let Some(readme) = ctx
+        .cache
+        .as_ref()
+        .and_then(|c| c.get(Endpoint::Readme, rid, sha))
+    {
+        return Ok::<_, Error>(signed_response(readme, signers));
+    }
+    let surf = Repository::open(repo.path())?;
+
+    for path in README_PATHS
         .iter()
         .map(ToString::to_string)
-        .chain(paths.iter().map(|p| p.to_lowercase()))
+        .chain(README_PATHS.iter().map(|p| p.to_lowercase()))
     {
-        if let Ok(blob) = repo.blob(sha, &path) {
+        if let Ok(blob) = surf.blob(sha, &path) {
             if blob.size() > MAX_BODY_LIMIT {
                 return Ok::<_, Error>(
                     (
@@ -558,136 +1184,556 @@ async fn readme_handler(
                 );
             }
 
-            return Ok::<_, Error>(
-                immutable_response(api::json::commit::Blob::new(&blob).as_json(&path))
-                    .into_response(),
-            );
+            let readme = api::json::commit::Blob::new(&blob)
+                .signed(&repo.backend)?
+                .as_json(&path);
+            if let Some(cache) = &ctx.cache {
+                cache.put(Endpoint::Readme, rid, sha, &readme);
+            }
+            return Ok::<_, Error>(signed_response(readme, signers));
         }
     }
 
     Err(Error::NotFound)
 }
 
-/// Get repo issues list.
-/// `GET /repos/:rid/issues`
-async fn issues_handler(
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeSearchQueryString {
+    pub q: Option<String>,
+    pub sha: Option<Oid>,
+    pub path: Option<String>,
+    #[serde(default)]
+    pub regex: bool,
+    pub context: Option<usize>,
+}
+
+/// Search the source tree of a repo.
+/// `GET /repos/:rid/search/code?q=<query>&sha=<sha>&path=<path>`
+///
+/// The query is matched literally against every line, unless `regex=true` is given.
+/// Binary files, files that aren't valid UTF-8 and files larger than `MAX_BODY_LIMIT` are
+/// skipped, and the search stops after `MAX_CODE_SEARCH_MATCHES` matching lines, in which
+/// case `truncated` is set.
+async fn code_search_handler(
     State(ctx): State<Context>,
+    session: Option<Session>,
     Path(rid): Path<RepoId>,
-    Query(qs): Query<CobsQuery<api::query::IssueStatus>>,
+    Query(qs): Query<CodeSearchQueryString>,
 ) -> impl IntoResponse {
-    let (repo, _) = ctx.repo(rid)?;
-    let CobsQuery {
-        page,
-        per_page,
-        status,
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
+    let CodeSearchQueryString {
+        q,
+        sha,
+        path,
+        regex,
+        context,
     } = qs;
-    let page = page.unwrap_or(0);
-    let per_page = per_page.unwrap_or(10);
-    let status = status.unwrap_or_default();
-    let issues = ctx.profile.issues(&repo)?;
-    let mut issues: Vec<_> = issues
-        .list()?
-        .filter_map(|r| {
-            let (id, issue) = r.ok()?;
-            (status.matches(issue.state())).then_some((id, issue))
-        })
-        .collect::<Vec<_>>();
+    let q = q.unwrap_or_default();
+    if q.is_empty() {
+        return Err(Error::BadRequest("query must not be empty".to_owned()));
+    }
+    let pattern = if regex {
+        regex::Regex::new(&q)?
+    } else {
+        regex::Regex::new(&regex::escape(&q))?
+    };
+    let context = context.unwrap_or(2).min(MAX_CODE_SEARCH_CONTEXT);
+    let path = path.unwrap_or_default();
+    let prefix = path.trim_matches('/');
 
-    issues.sort_by(|(_, a), (_, b)| b.timestamp().cmp(&a.timestamp()));
-    let aliases = &ctx.profile.aliases();
-    let issues = issues
-        .into_iter()
-        .map(|(id, issue)| api::json::cobs::Issue::new(&issue).as_json(id, aliases))
-        .skip(page * per_page)
-        .take(per_page)
-        .collect::<Vec<_>>();
+    // If the commit is provided, the response depends only on the query string and not on
+    // the state of the repository.
+    let is_immutable = sha.is_some();
+    let sha = match sha {
+        Some(sha) => sha,
+        None => repo.head()?.1,
+    };
 
-    Ok::<_, Error>(Json(issues))
-}
+    let commit = repo.backend.find_commit(*sha)?;
+    let mut tree = commit.tree()?;
+    if !prefix.is_empty() {
+        let entry = tree.get_path(std::path::Path::new(prefix)).map_err(|e| {
+            if radicle::git::is_not_found_err(&e) {
+                Error::NotFound
+            } else {
+                Error::from(e)
+            }
+        })?;
+        if entry.kind() != Some(ObjectType::Tree) {
+            return Err(Error::BadRequest(format!(
+                "path '{prefix}' is not a directory"
+            )));
+        }
+        tree = repo.backend.find_tree(entry.id())?;
+    }
 
-/// Get repo issue.
-/// `GET /repos/:rid/issues/:id`
-async fn issue_handler(
-    State(ctx): State<Context>,
-    Path((rid, issue_id)): Path<(RepoId, Oid)>,
-) -> impl IntoResponse {
-    let (repo, _) = ctx.repo(rid)?;
+    let mut blobs = Vec::new();
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() == Some(ObjectType::Blob) {
+            if let Some(name) = entry.name() {
+                blobs.push((format!("{root}{name}"), entry.id()));
+            }
+        }
+        TreeWalkResult::Ok
+    })?;
+
+    let mut files = Vec::new();
+    let mut total = 0;
+    let mut truncated = false;
+
+    for (name, oid) in blobs {
+        let blob = repo.backend.find_blob(oid)?;
+        if blob.is_binary() || blob.size() > MAX_BODY_LIMIT {
+            continue;
+        }
+        // Like the blob view, which sends files that aren't valid UTF-8 base64 encoded.
+        let Ok(content) = str::from_utf8(blob.content()) else {
+            continue;
+        };
+        let lines = content.lines().collect::<Vec<_>>();
+        let mut matches = Vec::new();
+
+        for (i, line) in lines.iter().enumerate() {
+            if !pattern.is_match(line) {
+                continue;
+            }
+            if total == MAX_CODE_SEARCH_MATCHES {
+                truncated = true;
+                break;
+            }
+            total += 1;
+            matches.push(json!({
+                "line": i + 1,
+                "content": line,
+                "before": lines[i.saturating_sub(context)..i],
+                "after": lines[i + 1..(i + 1 + context).min(lines.len())],
+            }));
+        }
+        if !matches.is_empty() {
+            let path = if prefix.is_empty() {
+                name
+            } else {
+                format!("{prefix}/{name}")
+            };
+            files.push(json!({ "path": path, "matches": matches }));
+        }
+        if truncated {
+            break;
+        }
+    }
+
+    let response = json!({ "sha": sha, "files": files, "truncated": truncated });
+
+    if is_immutable {
+        Ok::<_, Error>(immutable_response(response).into_response())
+    } else {
+        Ok::<_, Error>(Json(response).into_response())
+    }
+}
+
+/// Get repo issues list.
+/// `GET /repos/:rid/issues`
+async fn issues_handler(
+    State(ctx): State<Context>,
+    session: Option<Session>,
+    Path(rid): Path<RepoId>,
+    OriginalUri(uri): OriginalUri,
+    Query(qs): Query<CobsQuery<api::query::IssueStatus>>,
+) -> impl IntoResponse {
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
+    let filter = qs.filter(session.as_ref())?;
+    let pagination = Pagination::new(qs.cursor, qs.page, qs.per_page, 10);
+    let status = qs.status.unwrap_or_default();
+    let sort = qs.sort.unwrap_or_default();
+    let order = qs.order.unwrap_or_default();
+    let issues = ctx.profile.issues(&repo)?;
+    let mut issues: Vec<_> = issues
+        .list()?
+        .filter_map(|r| {
+            let (id, issue) = r.ok()?;
+            if !status.matches(issue.state()) {
+                return None;
+            }
+            let fields = CobFields::from(&issue);
+            if !filter.matches(&fields) {
+                return None;
+            }
+            let key = CobKey::new(id, order.key(sort.value(&fields)));
+
+            Some((key, (id, issue)))
+        })
+        .collect::<Vec<_>>();
+
+    issues.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let aliases = &ctx.profile.aliases();
+    let issues = pagination.sorted(issues)?.map(|(id, issue)| match qs.view {
+        View::Full => api::json::cobs::Issue::new(&issue).as_json(id, aliases),
+        View::Summary => api::json::cobs::Issue::new(&issue).as_summary_json(id, aliases),
+    });
+    let etag = ETag::new(rid)
+        .cobs(&repo, &issue::TYPENAME, None)?
+        .aliases(&issues.items)
+        .finish();
+
+    Ok::<_, Error>((issues.link(&uri), etag_response(etag, issues)))
+}
+
+/// Get repo issue.
+/// `GET /repos/:rid/issues/:id`
+async fn issue_handler(
+    State(ctx): State<Context>,
+    session: Option<Session>,
+    Path((rid, issue_id)): Path<(RepoId, Oid)>,
+) -> impl IntoResponse {
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
     let issue = ctx
         .profile
         .issues(&repo)?
         .get(&issue_id.into())?
         .ok_or(Error::NotFound)?;
-    let aliases = ctx.profile.aliases();
+    let issue =
+        api::json::cobs::Issue::new(&issue).as_json(issue_id.into(), &ctx.profile.aliases());
+    let etag = ETag::new(rid)
+        .cobs(&repo, &issue::TYPENAME, Some(&issue_id.into()))?
+        .aliases([&issue])
+        .finish();
+
+    Ok::<_, Error>(etag_response(etag, issue))
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueCreate {
+    pub title: String,
+    pub description: String,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    #[serde(default)]
+    pub assignees: Vec<Did>,
+    #[serde(default)]
+    pub embeds: Vec<Embed<Uri>>,
+}
+
+/// Create a new issue.
+/// `POST /repos/:rid/issues`
+async fn issue_create_handler(
+    State(ctx): State<Context>,
+    Path(rid): Path<RepoId>,
+    session: Session,
+    Json(issue): Json<IssueCreate>,
+) -> impl IntoResponse {
+    let signer = ctx.signer(&session)?;
+    let (repo, _) = ctx.repo(rid, Some(&session))?;
+    let mut issues = ctx.profile.issues_mut(&repo)?;
+    let issue = issues.create(
+        issue.title,
+        issue.description,
+        &issue.labels,
+        &issue.assignees,
+        issue.embeds,
+        &signer,
+    )?;
+    let id = *issue.id();
+    ctx.announce_refs(rid);
 
-    Ok::<_, Error>(Json(
-        api::json::cobs::Issue::new(&issue).as_json(issue_id.into(), &aliases),
+    Ok::<_, Error>((
+        StatusCode::CREATED,
+        Json(json!({ "success": true, "id": id })),
     ))
 }
 
+/// Update an issue.
+/// `PATCH /repos/:rid/issues/:id`
+///
+/// The request body is an issue action, eg. `{ "type": "lifecycle", "state": { "status": "closed", "reason": "solved" } }`.
+async fn issue_update_handler(
+    State(ctx): State<Context>,
+    Path((rid, issue_id)): Path<(RepoId, Oid)>,
+    session: Session,
+    Json(action): Json<issue::Action>,
+) -> impl IntoResponse {
+    let signer = ctx.signer(&session)?;
+    let (repo, _) = ctx.repo(rid, Some(&session))?;
+    let mut issues = ctx.profile.issues_mut(&repo)?;
+    let mut issue = issues.get_mut(&issue_id.into())?;
+    let id = match action {
+        issue::Action::Assign { assignees } => issue.assign(assignees, &signer)?,
+        issue::Action::Edit { title } => issue.edit(title, &signer)?,
+        issue::Action::Lifecycle { state } => issue.lifecycle(state, &signer)?,
+        issue::Action::Label { labels } => issue.label(labels, &signer)?,
+        issue::Action::Comment {
+            body,
+            reply_to,
+            embeds,
+        } => {
+            let reply_to = reply_to.unwrap_or(*issue.root().0);
+            issue.comment(body, reply_to, embeds, &signer)?
+        }
+        issue::Action::CommentEdit { id, body, embeds } => {
+            issue.edit_comment(id, body, embeds, &signer)?
+        }
+        issue::Action::CommentRedact { id } => issue.redact_comment(id, &signer)?,
+        issue::Action::CommentReact {
+            id,
+            reaction,
+            active,
+        } => issue.react(id, reaction, active, &signer)?,
+    };
+    ctx.announce_refs(rid);
+
+    Ok::<_, Error>(Json(json!({ "success": true, "id": id })))
+}
+
 /// Get repo patches list.
 /// `GET /repos/:rid/patches`
 async fn patches_handler(
     State(ctx): State<Context>,
+    session: Option<Session>,
     Path(rid): Path<RepoId>,
-    Query(qs): Query<CobsQuery<api::query::PatchStatus>>,
+    OriginalUri(uri): OriginalUri,
+    Query(qs): Query<CobsQuery<api::query::PatchStatus, api::query::PatchSort>>,
+    Query(patch_qs): Query<PatchQuery>,
 ) -> impl IntoResponse {
-    let (repo, _) = ctx.repo(rid)?;
-    let CobsQuery {
-        page,
-        per_page,
-        status,
-    } = qs;
-    let page = page.unwrap_or(0);
-    let per_page = per_page.unwrap_or(10);
-    let status = status.unwrap_or_default();
+    let (repo, doc) = ctx.repo(rid, session.as_ref())?;
+    let default_branch = doc.project().ok().map(|p| p.default_branch().to_string());
+    let filter = qs.filter(session.as_ref())?;
+    let pagination = Pagination::new(qs.cursor, qs.page, qs.per_page, 10);
+    let status = qs.status.unwrap_or_default();
+    let sort = qs.sort.unwrap_or_default();
+    let order = qs.order.unwrap_or_default();
     let patches = ctx.profile.patches(&repo)?;
     let mut patches = patches
         .list()?
         .filter_map(|r| {
             let (id, patch) = r.ok()?;
-            (status.matches(patch.state())).then_some((id, patch))
+            if !status.matches(patch.state()) {
+                return None;
+            }
+            let fields = CobFields::from(&patch);
+            if !filter.matches(&fields) || !patch_qs.matches(&patch, default_branch.as_deref()) {
+                return None;
+            }
+            let key = CobKey::new(id, order.key(sort.value(&patch, &fields)));
+
+            Some((key, (id, patch)))
         })
         .collect::<Vec<_>>();
-    patches.sort_by(|(_, a), (_, b)| b.timestamp().cmp(&a.timestamp()));
+    patches.sort_by(|(a, _), (b, _)| a.cmp(b));
     let aliases = ctx.profile.aliases();
-    let patches = patches
-        .into_iter()
-        .map(|(id, patch)| api::json::cobs::Patch::new(&patch).as_json(id, &repo, &aliases))
-        .skip(page * per_page)
-        .take(per_page)
-        .collect::<Vec<_>>();
+    let patches = pagination
+        .sorted(patches)?
+        .map(|(id, patch)| match qs.view {
+            View::Full => api::json::cobs::Patch::new(&patch).as_json(id, &repo, &aliases),
+            View::Summary => api::json::cobs::Patch::new(&patch).as_summary_json(id, &aliases),
+        });
+    let etag = ETag::new(rid)
+        .cobs(&repo, &patch::TYPENAME, None)?
+        .aliases(&patches.items)
+        .finish();
 
-    Ok::<_, Error>(Json(patches))
+    Ok::<_, Error>((patches.link(&uri), etag_response(etag, patches)))
 }
 
 /// Get repo patch.
 /// `GET /repos/:rid/patches/:id`
 async fn patch_handler(
     State(ctx): State<Context>,
+    session: Option<Session>,
     Path((rid, patch_id)): Path<(RepoId, Oid)>,
 ) -> impl IntoResponse {
-    let (repo, _) = ctx.repo(rid)?;
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
     let patches = ctx.profile.patches(&repo)?;
     let patch = patches.get(&patch_id.into())?.ok_or(Error::NotFound)?;
-    let aliases = ctx.profile.aliases();
+    let patch =
+        api::json::cobs::Patch::new(&patch).as_json(patch_id.into(), &repo, &ctx.profile.aliases());
+    let etag = ETag::new(rid)
+        .cobs(&repo, &patch::TYPENAME, Some(&patch_id.into()))?
+        .aliases([&patch])
+        .finish();
+
+    Ok::<_, Error>(etag_response(etag, patch))
+}
+
+/// Get the range-diff between two revisions of a patch.
+/// `GET /repos/:rid/patches/:id/revisions/:rev/interdiff/:other`
+async fn interdiff_handler(
+    State(ctx): State<Context>,
+    session: Option<Session>,
+    Path((rid, patch_id, rev, other)): Path<(RepoId, Oid, Oid, Oid)>,
+) -> impl IntoResponse {
+    let (repo, _) = ctx.repo(rid, session.as_ref())?;
+    let patches = ctx.profile.patches(&repo)?;
+    let patch = patches.get(&patch_id.into())?.ok_or(Error::NotFound)?;
+    let old = patch.revision(&rev.into()).ok_or(Error::NotFound)?;
+    let new = patch.revision(&other.into()).ok_or(Error::NotFound)?;
+
+    // Revisions can't be changed, only redacted, in which case they are no longer found.
+    let response = ctx.cached(Endpoint::Interdiff, rid, format!("{rev}..{other}"), || {
+        let surf = Repository::open(repo.path())?;
+        let pairs = api::interdiff::range_diff(
+            &repo.backend,
+            (*old.base().as_ref(), *old.head().as_ref()),
+            (*new.base().as_ref(), *new.head().as_ref()),
+        )?;
+        let commit = |oid: Option<git2::Oid>| {
+            oid.map(|oid| {
+                let commit = surf.commit(Oid::from(oid))?;
+                let commit = api::json::commit::Commit::new(&commit).signed(&repo.backend)?;
+                Ok::<_, Error>(commit.as_json())
+            })
+            .transpose()
+        };
+        let commits = pairs
+            .into_iter()
+            .map(|pair| {
+                Ok::<_, Error>(json!({
+                    "status": pair.status.as_str(),
+                    "old": commit(pair.old)?,
+                    "new": commit(pair.new)?,
+                    "diff": pair.diff.as_ref().map(|d| api::json::diff::Diff::new(d).as_json()),
+                }))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(json!({
+            "old": { "id": rev, "base": old.base(), "head": old.head() },
+            "new": { "id": other, "base": new.base(), "head": new.head() },
+            "commits": commits,
+        }))
+    })?;
+
+    Ok::<_, Error>(signed_response(response, |r| {
+        let aliases = ctx.profile.aliases();
+        let mut signed = false;
+
+        for pair in r["commits"].as_array_mut().into_iter().flatten() {
+            for side in ["old", "new"] {
+                signed |= api::json::commit::Commit::with_signer(&mut pair[side], &aliases);
+            }
+        }
+        signed
+    }))
+}
+
+/// Comment on, review, react to, archive or redraft a patch.
+/// `PATCH /repos/:rid/patches/:id`
+///
+/// The request body is a patch action, eg. `{ "type": "lifecycle", "state": { "status": "archived" } }`.
+/// Actions that edit the patch itself, or that add, edit or merge revisions, are rejected.
+async fn patch_update_handler(
+    State(ctx): State<Context>,
+    Path((rid, patch_id)): Path<(RepoId, Oid)>,
+    session: Session,
+    Json(action): Json<patch::Action>,
+) -> impl IntoResponse {
+    let signer = ctx.signer(&session)?;
+    let (repo, _) = ctx.repo(rid, Some(&session))?;
+    let mut patches = ctx.profile.patches_mut(&repo)?;
+    let mut patch = patches.get_mut(&patch_id.into())?;
+    let id = match action {
+        patch::Action::Lifecycle { state } => patch.lifecycle(state, &signer)?,
+        patch::Action::Review {
+            revision,
+            summary,
+            verdict,
+            labels,
+        } => *patch.review(revision, verdict, summary, labels, &signer)?,
+        patch::Action::ReviewEdit {
+            review,
+            summary,
+            verdict,
+            labels,
+        } => patch.review_edit(review, verdict, summary, labels, &signer)?,
+        patch::Action::ReviewRedact { review } => patch.redact_review(review, &signer)?,
+        patch::Action::ReviewComment {
+            review,
+            body,
+            location,
+            reply_to,
+            embeds,
+        } => patch.review_comment(review, body, location, reply_to, embeds, &signer)?,
+        patch::Action::ReviewCommentEdit {
+            review,
+            comment,
+            body,
+            embeds,
+        } => patch.edit_review_comment(review, comment, body, embeds, &signer)?,
+        patch::Action::ReviewCommentRedact { review, comment } => {
+            patch.redact_review_comment(review, comment, &signer)?
+        }
+        patch::Action::ReviewCommentReact {
+            review,
+            comment,
+            reaction,
+            active,
+        } => patch.react_review_comment(review, comment, reaction, active, &signer)?,
+        patch::Action::ReviewCommentResolve { review, comment } => {
+            patch.resolve_review_comment(review, comment, &signer)?
+        }
+        patch::Action::ReviewCommentUnresolve { review, comment } => {
+            patch.unresolve_review_comment(review, comment, &signer)?
+        }
+        patch::Action::RevisionReact {
+            revision,
+            location,
+            reaction,
+            active,
+        } => patch.react(revision, reaction, location, active, &signer)?,
+        patch::Action::RevisionComment {
+            revision,
+            location,
+            body,
+            reply_to,
+            embeds,
+        } => patch.comment(revision, body, reply_to, location, embeds, &signer)?,
+        patch::Action::RevisionCommentEdit {
+            revision,
+            comment,
+            body,
+            embeds,
+        } => patch.comment_edit(revision, comment, body, embeds, &signer)?,
+        patch::Action::RevisionCommentRedact { revision, comment } => {
+            patch.comment_redact(revision, comment, &signer)?
+        }
+        patch::Action::RevisionCommentReact {
+            revision,
+            comment,
+            reaction,
+            active,
+        } => patch.comment_react(revision, comment, reaction, active, &signer)?,
+        // Merges and revisions refer to commits, which are pushed over git rather than
+        // recorded through the API.
+        patch::Action::Edit { .. }
+        | patch::Action::Label { .. }
+        | patch::Action::Assign { .. }
+        | patch::Action::Merge { .. }
+        | patch::Action::Revision { .. }
+        | patch::Action::RevisionEdit { .. }
+        | patch::Action::RevisionRedact { .. } => {
+            return Err(Error::BadRequest(String::from("unsupported patch action")));
+        }
+    };
+    ctx.announce_refs(rid);
 
-    Ok::<_, Error>(Json(api::json::cobs::Patch::new(&patch).as_json(
-        patch_id.into(),
-        &repo,
-        &aliases,
-    )))
+    Ok::<_, Error>(Json(json!({ "success": true, "id": id })))
 }
 
 #[cfg(test)]
 mod routes {
     use std::net::SocketAddr;
 
+    use axum::body::Body;
     use axum::extract::connect_info::MockConnectInfo;
-    use axum::http::StatusCode;
+    use axum::http::{header, StatusCode};
     use pretty_assertions::assert_eq;
     use radicle::storage::ReadStorage;
-    use serde_json::json;
+    use serde_json::{json, Value};
 
+    use radicle::cob::identity::Identity;
+    use radicle::identity::Did;
+
+    use crate::api::pagination::MAX_PER_PAGE;
     use crate::test::*;
 
     #[tokio::test]
@@ -701,80 +1747,84 @@ mod routes {
         assert_eq!(response.status(), StatusCode::OK);
         assert_eq!(
             response.json().await,
-            json!([
-              {
-                "payloads": {
-                  "xyz.radicle.project": {
-                    "data": {
-                      "defaultBranch": "master",
-                      "description": "Rad repository for tests",
-                      "name": "hello-world",
-                    },
-                    "meta": {
-                      "head": HEAD,
-                      "patches": {
-                        "open": 1,
-                        "draft": 0,
-                        "archived": 0,
-                        "merged": 0,
-                      },
-                      "issues": {
-                        "open": 1,
-                        "closed": 0,
+            json!({
+              "items": [
+                {
+                  "payloads": {
+                    "xyz.radicle.project": {
+                      "data": {
+                        "defaultBranch": "master",
+                        "description": "Rad repository for tests",
+                        "name": "hello-world",
                       },
+                      "meta": {
+                        "head": HEAD,
+                        "patches": {
+                          "open": 1,
+                          "draft": 0,
+                          "archived": 0,
+                          "merged": 0,
+                        },
+                        "issues": {
+                          "open": 1,
+                          "closed": 0,
+                        },
+                      }
                     }
-                  }
-                },
-                "delegates": [
-                  {
-                    "id": DID,
-                    "alias": CONTRIBUTOR_ALIAS
                   },
-                ],
-                "threshold": 1,
-                "visibility": {
-                  "type": "public"
-                },
-                "rid": RID,
-                "seeding": 1,
-              },
-              {
-                "payloads": {
-                  "xyz.radicle.project": {
-                    "data": {
-                      "defaultBranch": "master",
-                      "description": "Rad repository for sorting",
-                      "name": "again-hello-world",
+                  "delegates": [
+                    {
+                      "id": DID,
+                      "alias": CONTRIBUTOR_ALIAS
                     },
-                    "meta": {
-                      "head": "344dcd184df5bf37aab6c107fa9371a1c5b3321a",
-                      "patches": {
-                        "open": 0,
-                        "draft": 0,
-                        "archived": 0,
-                        "merged": 0,
-                      },
-                      "issues": {
-                        "open": 0,
-                        "closed": 0,
+                  ],
+                  "threshold": 1,
+                  "visibility": {
+                    "type": "public"
+                  },
+                  "rid": RID,
+                  "seeding": 1,
+                },
+                {
+                  "payloads": {
+                    "xyz.radicle.project": {
+                      "data": {
+                        "defaultBranch": "master",
+                        "description": "Rad repository for sorting",
+                        "name": "again-hello-world",
                       },
+                      "meta": {
+                        "head": "344dcd184df5bf37aab6c107fa9371a1c5b3321a",
+                        "patches": {
+                          "open": 0,
+                          "draft": 0,
+                          "archived": 0,
+                          "merged": 0,
+                        },
+                        "issues": {
+                          "open": 0,
+                          "closed": 0,
+                        },
+                      }
                     }
-                  }
-                },
-                "delegates": [
-                  {
-                    "id": DID,
-                    "alias": CONTRIBUTOR_ALIAS
-                  }
-                ],
-                "threshold": 1,
-                "visibility": {
-                  "type": "public"
+                  },
+                  "delegates": [
+                    {
+                      "id": DID,
+                      "alias": CONTRIBUTOR_ALIAS
+                    }
+                  ],
+                  "threshold": 1,
+                  "visibility": {
+                    "type": "public"
+                  },
+                  "rid": "rad:z4GypKmh1gkEfmkXtarcYnkvtFUfE",
+                  "seeding": 1,
                 },
-                "rid": "rad:z4GypKmh1gkEfmkXtarcYnkvtFUfE",
-                "seeding": 1,
-              },
-            ])
+              ],
+              "total": 2,
+              "nextCursor": null,
+            })
         );
 
         let app = super::router(seed).layer(MockConnectInfo(SocketAddr::from((
@@ -786,80 +1836,84 @@ mod routes {
         assert_eq!(response.status(), StatusCode::OK);
         assert_eq!(
             response.json().await,
-            json!([
-              {
-                "payloads": {
-                  "xyz.radicle.project": {
-                    "data": {
-                      "defaultBranch": "master",
-                      "description": "Rad repository for tests",
-                      "name": "hello-world",
-                    },
-                    "meta": {
-                      "head": HEAD,
-                      "patches": {
-                        "open": 1,
-                        "draft": 0,
-                        "archived": 0,
-                        "merged": 0,
-                      },
-                      "issues": {
-                        "open": 1,
-                        "closed": 0,
+            json!({
+              "items": [
+                {
+                  "payloads": {
+                    "xyz.radicle.project": {
+                      "data": {
+                        "defaultBranch": "master",
+                        "description": "Rad repository for tests",
+                        "name": "hello-world",
                       },
+                      "meta": {
+                        "head": HEAD,
+                        "patches": {
+                          "open": 1,
+                          "draft": 0,
+                          "archived": 0,
+                          "merged": 0,
+                        },
+                        "issues": {
+                          "open": 1,
+                          "closed": 0,
+                        },
+                      }
                     }
-                  }
-                },
-                "delegates": [
-                  {
-                    "id": DID,
-                    "alias": CONTRIBUTOR_ALIAS
-                  }
-                ],
-                "threshold": 1,
-                "visibility": {
-                  "type": "public"
+                  },
+                  "delegates": [
+                    {
+                      "id": DID,
+                      "alias": CONTRIBUTOR_ALIAS
+                    }
+                  ],
+                  "threshold": 1,
+                  "visibility": {
+                    "type": "public"
+                  },
+                  "rid": RID,
+                  "seeding": 1,
                 },
-                "rid": RID,
-                "seeding": 1,
-              },
-              {
-                "payloads": {
-                  "xyz.radicle.project": {
-                    "data": {
-                      "name": "again-hello-world",
-                      "description": "Rad repository for sorting",
-                      "defaultBranch": "master",
-                    },
-                    "meta": {
-                      "head": "344dcd184df5bf37aab6c107fa9371a1c5b3321a",
-                      "patches": {
-                        "open": 0,
-                        "draft": 0,
-                        "archived": 0,
-                        "merged": 0,
-                      },
-                      "issues": {
-                        "open": 0,
-                        "closed": 0,
+                {
+                  "payloads": {
+                    "xyz.radicle.project": {
+                      "data": {
+                        "name": "again-hello-world",
+                        "description": "Rad repository for sorting",
+                        "defaultBranch": "master",
                       },
+                      "meta": {
+                        "head": "344dcd184df5bf37aab6c107fa9371a1c5b3321a",
+                        "patches": {
+                          "open": 0,
+                          "draft": 0,
+                          "archived": 0,
+                          "merged": 0,
+                        },
+                        "issues": {
+                          "open": 0,
+                          "closed": 0,
+                        },
+                      }
                     }
-                  }
-                },
-                "delegates": [
-                  {
-                    "id": DID,
-                    "alias": CONTRIBUTOR_ALIAS
                   },
-                ],
-                "threshold": 1,
-                "visibility": {
-                  "type": "public"
+                  "delegates": [
+                    {
+                      "id": DID,
+                      "alias": CONTRIBUTOR_ALIAS
+                    },
+                  ],
+                  "threshold": 1,
+                  "visibility": {
+                    "type": "public"
+                  },
+                  "rid": "rad:z4GypKmh1gkEfmkXtarcYnkvtFUfE",
+                  "seeding": 1,
                 },
-                "rid": "rad:z4GypKmh1gkEfmkXtarcYnkvtFUfE",
-                "seeding": 1,
-              },
-            ])
+              ],
+              "total": 2,
+              "nextCursor": null,
+            })
         );
     }
 
@@ -920,146 +1974,533 @@ mod routes {
         assert_eq!(response.status(), StatusCode::OK);
         assert_eq!(
             response.json().await,
-            json!([
-              {
-                "payloads": {
-                  "xyz.radicle.project": {
-                    "name": "hello-world",
-                    "description": "Rad repository for tests",
-                    "defaultBranch": "master",
-                  }
+            json!({
+              "items": [
+                {
+                  "payloads": {
+                    "xyz.radicle.project": {
+                      "name": "hello-world",
+                      "description": "Rad repository for tests",
+                      "defaultBranch": "master",
+                    }
+                  },
+                  "rid": "rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp",
+                  "delegates": [
+                    {
+                      "id": DID,
+                      "alias": CONTRIBUTOR_ALIAS
+                    }
+                  ],
+                  "seeds": 1,
                 },
-                "rid": "rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp",
-                "delegates": [
-                  {
-                    "id": DID,
-                    "alias": CONTRIBUTOR_ALIAS
-                  }
-                ],
-                "seeds": 1,
-              },
-              {
-                "payloads": {
-                  "xyz.radicle.project": {
-                    "name": "again-hello-world",
-                    "description": "Rad repository for sorting",
-                    "defaultBranch": "master",
+                {
+                  "payloads": {
+                    "xyz.radicle.project": {
+                      "name": "again-hello-world",
+                      "description": "Rad repository for sorting",
+                      "defaultBranch": "master",
+                    },
+                  },
+                  "rid": "rad:z4GypKmh1gkEfmkXtarcYnkvtFUfE",
+                  "delegates": [
+                    {
+                      "id": DID,
+                      "alias": CONTRIBUTOR_ALIAS
+                    },
+                  ],
+                  "seeds": 1,
+                },
+              ],
+              "total": 2,
+              "nextCursor": null,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_repos_pagination() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(seed(tmp.path()));
+        let response = get(&app, "/repos/search?q=hello&perPage=1").await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.json().await,
+            json!({
+              "items": [
+                {
+                  "rid": "rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp",
+                  "payloads": {
+                    "xyz.radicle.project": {
+                      "defaultBranch": "master",
+                      "description": "Rad repository for tests",
+                      "name": "hello-world",
+                    },
+                  },
+                  "delegates": [
+                    {
+                      "id": DID,
+                      "alias": CONTRIBUTOR_ALIAS,
+                    }
+                  ],
+                  "seeds": 1,
+                },
+              ],
+              "total": 2,
+              "nextCursor": "rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_repos_description_and_readme() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(seed(tmp.path()));
+
+        // Only found in the description.
+        let response = get(&app, "/repos/search?q=sorting").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.json().await,
+            json!({
+              "items": [
+                {
+                  "payloads": {
+                    "xyz.radicle.project": {
+                      "name": "again-hello-world",
+                      "description": "Rad repository for sorting",
+                      "defaultBranch": "master",
+                    },
                   },
+                  "rid": "rad:z4GypKmh1gkEfmkXtarcYnkvtFUfE",
+                  "delegates": [
+                    {
+                      "id": DID,
+                      "alias": CONTRIBUTOR_ALIAS
+                    },
+                  ],
+                  "seeds": 1,
+                },
+              ],
+              "total": 1,
+              "nextCursor": null,
+            })
+        );
+
+        // Found in the README and the delegate alias, with a prefix match on the last term.
+        let response = get(&app, "/repos/search?q=hello%20world%20again%20se").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let rids = response.json().await["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["rid"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(rids, vec![json!("rad:z4GypKmh1gkEfmkXtarcYnkvtFUfE")]);
+
+        // Private repositories are never returned.
+        let response = get(&app, "/repos/search?q=private").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.json().await,
+            json!({
+              "items": [],
+              "total": 0,
+              "nextCursor": null,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repos_not_found() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(seed(tmp.path()));
+        let response = get(&app, "/repos/rad:z2u2CP3ZJzB7ZqE8jHrau19yjcfCQ").await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_repos_identity() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+
+        let signer = radicle::node::device::Device::mock_from_seed([0xff; 32]);
+        let delegate = radicle::node::device::Device::mock_from_seed([0xaa; 32]);
+        let delegate = Did::from(*delegate.public_key());
+        let repo = ctx
+            .profile()
+            .storage
+            .repository(RID.parse().unwrap())
+            .unwrap();
+        let mut identity = Identity::load_mut(&repo).unwrap();
+        let doc = identity
+            .doc()
+            .clone()
+            .with_edits(|doc| doc.delegate(delegate))
+            .unwrap();
+        identity
+            .update("Add a delegate", "", &doc, &signer)
+            .unwrap();
+
+        let response = get(&app, format!("/repos/{RID}/identity")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let page = response.json().await;
+        assert_eq!(page["total"], 2);
+
+        let root = &page["items"][0];
+        assert_eq!(root["title"], "Initial revision");
+        assert_eq!(root["state"], "accepted");
+        assert_eq!(root["parent"], json!(null));
+        assert_eq!(root["verdicts"][0]["author"]["id"], DID);
+        assert_eq!(root["verdicts"][0]["verdict"], "accept");
+
+        let revision = &page["items"][1];
+        assert_eq!(revision["title"], "Add a delegate");
+        assert_eq!(revision["state"], "accepted");
+        assert_eq!(revision["parent"], root["id"]);
+        assert_eq!(
+            revision["changes"],
+            json!({
+                "delegates": {
+                    "added": [{ "id": delegate }],
+                    "removed": [],
                 },
-                "rid": "rad:z4GypKmh1gkEfmkXtarcYnkvtFUfE",
-                "delegates": [
+                "threshold": null,
+                "visibility": null,
+            })
+        );
+        assert!(revision["diff"]
+            .as_str()
+            .unwrap()
+            .contains(&format!("+    \"{delegate}\"\n")));
+    }
+
+    #[tokio::test]
+    async fn test_repos_commits_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(seed(tmp.path()));
+        let response = get(&app, format!("/repos/{RID}/commits")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.json().await,
+            json!({
+              "items": [
                   {
-                    "id": DID,
-                    "alias": CONTRIBUTOR_ALIAS
+                    "id": HEAD,
+                    "author": {
+                      "name": "Alice Liddell",
+                      "email": "alice@radicle.xyz"
+                    },
+                    "summary": "Add another folder",
+                    "description": "",
+                    "parents": [
+                      "ee8d6a29304623a78ebfa5eeed5af674d0e58f83",
+                    ],
+                    "committer": {
+                      "name": "Alice Liddell",
+                      "email": "alice@radicle.xyz",
+                      "time": 1673003014
+                    },
+                    "signature": null,
                   },
-                ],
-                "seeds": 1,
-              },
-            ])
+                  {
+                    "id": PARENT,
+                    "author": {
+                      "name": "Alice Liddell",
+                      "email": "alice@radicle.xyz"
+                    },
+                    "summary": "Add contributing file",
+                    "description": "",
+                    "parents": [
+                      "f604ce9fd5b7cc77b7609beda45ea8760bee78f7",
+                    ],
+                    "committer": {
+                      "name": "Alice Liddell",
+                      "email": "alice@radicle.xyz",
+                      "time": 1673002014,
+                    },
+                    "signature": null,
+                  },
+                  {
+                    "id": INITIAL_COMMIT,
+                    "author": {
+                      "name": "Alice Liddell",
+                      "email": "alice@radicle.xyz",
+                    },
+                    "summary": "Initial commit",
+                    "description": "",
+                    "parents": [],
+                    "committer": {
+                      "name": "Alice Liddell",
+                      "email": "alice@radicle.xyz",
+                      "time": 1673001014,
+                    },
+                    "signature": null,
+                  },
+              ],
+              "total": 3,
+              "nextCursor": null,
+            })
         );
     }
 
     #[tokio::test]
-    async fn test_search_repos_pagination() {
+    async fn test_repos_commits_pagination() {
         let tmp = tempfile::tempdir().unwrap();
         let app = super::router(seed(tmp.path()));
-        let response = get(&app, "/repos/search?q=hello&perPage=1").await;
+        let response = get(&app, format!("/repos/{RID}/commits?perPage=2")).await;
 
         assert_eq!(response.status(), StatusCode::OK);
         assert_eq!(
-            response.json().await,
-            json!([
-              {
-                "rid": "rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp",
-                "payloads": {
-                  "xyz.radicle.project": {
-                    "defaultBranch": "master",
-                    "description": "Rad repository for tests",
-                    "name": "hello-world",
-                  },
-                },
-                "delegates": [
-                  {
-                    "id": DID,
-                    "alias": CONTRIBUTOR_ALIAS,
-                  }
-                ],
-                "seeds": 1,
-              },
-            ])
+            response.headers().get(header::LINK).unwrap(),
+            &format!("</repos/{RID}/commits?perPage=2&cursor={PARENT}>; rel=\"next\"")
         );
+        let page = response.json().await;
+        let ids = page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["id"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![json!(HEAD), json!(PARENT)]);
+        assert_eq!(page["total"], 3);
+        assert_eq!(page["nextCursor"], PARENT);
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/commits?perPage=2&cursor={PARENT}"),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(header::LINK).is_none());
+        let page = response.json().await;
+        assert_eq!(page["items"][0]["id"], INITIAL_COMMIT);
+        assert_eq!(page["total"], 3);
+        assert_eq!(page["nextCursor"], json!(null));
+
+        let response = get(&app, format!("/repos/{RID}/commits?cursor=foo")).await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
     #[tokio::test]
-    async fn test_repos_not_found() {
+    async fn test_repos_commits_since() {
         let tmp = tempfile::tempdir().unwrap();
-        let app = super::router(seed(tmp.path()));
-        let response = get(&app, "/repos/rad:z2u2CP3ZJzB7ZqE8jHrau19yjcfCQ").await;
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let repo = ctx
+            .profile()
+            .storage
+            .repository(RID.parse().unwrap())
+            .unwrap();
+        let git = &repo.backend;
+        let mut parent = git.find_commit(HEAD.parse().unwrap()).unwrap();
+        let tree = parent.tree().unwrap();
+        let sig = parent.author().to_owned();
 
-        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        for i in 0..MAX_PER_PAGE {
+            let oid = git
+                .commit(None, &sig, &sig, &format!("Commit {i}"), &tree, &[&parent])
+                .unwrap();
+            parent = git.find_commit(oid).unwrap();
+        }
+        let tip = parent.id();
+
+        // Time ranges return all their commits, unless a page size is requested.
+        let response = get(&app, format!("/repos/{RID}/commits?parent={tip}&since=0")).await;
+        let page = response.json().await;
+        assert_eq!(page["items"].as_array().unwrap().len(), MAX_PER_PAGE + 3);
+        assert_eq!(page["nextCursor"], json!(null));
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/commits?parent={tip}&since=0&perPage=1000"),
+        )
+        .await;
+        let page = response.json().await;
+        assert_eq!(page["items"].as_array().unwrap().len(), MAX_PER_PAGE);
     }
 
     #[tokio::test]
-    async fn test_repos_commits_root() {
+    async fn test_repos_commits_path() {
         let tmp = tempfile::tempdir().unwrap();
-        let app = super::router(seed(tmp.path()));
-        let response = get(&app, format!("/repos/{RID}/commits")).await;
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let ids = |page: Value| {
+            page["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|c| c["id"].as_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        };
 
+        let response = get(&app, format!("/repos/{RID}/commits?path=README")).await;
         assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(ids(response.json().await), [HEAD, PARENT, INITIAL_COMMIT]);
+
+        // Counting the commits that changed a path would mean diffing the whole history.
+        let response = get(&app, format!("/repos/{RID}/commits?path=README&perPage=1")).await;
+        let page = response.json().await;
+        assert_eq!(page.get("total"), None);
+        assert_eq!(page["nextCursor"], HEAD);
+        assert_eq!(ids(page), [HEAD]);
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/commits?path=README&perPage=1&cursor={HEAD}"),
+        )
+        .await;
+        assert_eq!(ids(response.json().await), [PARENT]);
+
+        let response = get(&app, format!("/repos/{RID}/commits?path=dir1/")).await;
+        assert_eq!(ids(response.json().await), [HEAD]);
+
+        let response = get(&app, format!("/repos/{RID}/commits?path=CONTRIBUTING")).await;
+        assert_eq!(ids(response.json().await), [HEAD, PARENT]);
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/commits?path=README&until=1673002014"),
+        )
+        .await;
+        assert_eq!(ids(response.json().await), [INITIAL_COMMIT]);
+
+        // Rename `dir1/README` to `dir2/README`.
+        let repo = ctx
+            .profile()
+            .storage
+            .repository(RID.parse().unwrap())
+            .unwrap();
+        let git = &repo.backend;
+        let parent = git.find_commit(HEAD.parse().unwrap()).unwrap();
+        let dir = parent.tree().unwrap().get_name("dir1").unwrap().id();
+        let mut tree = git.treebuilder(Some(&parent.tree().unwrap())).unwrap();
+        tree.remove("dir1").unwrap();
+        tree.insert("dir2", dir, 0o040000).unwrap();
+        let tree = git.find_tree(tree.write().unwrap()).unwrap();
+        let sig = parent.author();
+        let head = git
+            .commit(None, &sig, &sig, "Rename folder\n", &tree, &[&parent])
+            .unwrap();
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/commits?parent={head}&path=dir2/README"),
+        )
+        .await;
+        assert_eq!(ids(response.json().await), [head.to_string()]);
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/commits?parent={head}&path=dir2/README&follow=true"),
+        )
+        .await;
         assert_eq!(
-            response.json().await,
-            json!([
-                {
-                  "id": HEAD,
-                  "author": {
-                    "name": "Alice Liddell",
-                    "email": "alice@radicle.xyz"
-                  },
-                  "summary": "Add another folder",
-                  "description": "",
-                  "parents": [
-                    "ee8d6a29304623a78ebfa5eeed5af674d0e58f83",
-                  ],
-                  "committer": {
-                    "name": "Alice Liddell",
-                    "email": "alice@radicle.xyz",
-                    "time": 1673003014
-                  },
-                },
-                {
-                  "id": PARENT,
-                  "author": {
-                    "name": "Alice Liddell",
-                    "email": "alice@radicle.xyz"
-                  },
-                  "summary": "Add contributing file",
-                  "description": "",
-                  "parents": [
-                    "f604ce9fd5b7cc77b7609beda45ea8760bee78f7",
-                  ],
-                  "committer": {
-                    "name": "Alice Liddell",
-                    "email": "alice@radicle.xyz",
-                    "time": 1673002014,
-                  },
-                },
-                {
-                  "id": INITIAL_COMMIT,
-                  "author": {
-                    "name": "Alice Liddell",
-                    "email": "alice@radicle.xyz",
-                  },
-                  "summary": "Initial commit",
-                  "description": "",
-                  "parents": [],
-                  "committer": {
-                    "name": "Alice Liddell",
-                    "email": "alice@radicle.xyz",
-                    "time": 1673001014,
-                  },
-                },
-            ])
+            ids(response.json().await),
+            [head.to_string(), HEAD.to_owned()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repos_commits_signed() {
+        use radicle::crypto::signature::Signer as _;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+
+        let signer = radicle::node::device::Device::mock_from_seed([0xff; 32]);
+        let repo = ctx
+            .profile()
+            .storage
+            .repository(RID.parse().unwrap())
+            .unwrap();
+        let git = &repo.backend;
+        let parent = git.find_commit(HEAD.parse().unwrap()).unwrap();
+        let tree = parent.tree().unwrap();
+        let sig = parent.author();
+
+        // A commit signed with SSH, as `git commit -S` does.
+        let buffer = git
+            .commit_create_buffer(&sig, &sig, "Signed commit\n", &tree, &[&parent])
+            .unwrap();
+        let data = ssh_key::SshSig::signed_data("git", ssh_key::HashAlg::Sha512, &buffer).unwrap();
+        let signature: radicle::crypto::Signature = signer.sign(&data);
+        let pem = ssh_key::SshSig::new(
+            ssh_key::PublicKey::from(*signer.public_key())
+                .key_data()
+                .clone(),
+            "git",
+            ssh_key::HashAlg::Sha512,
+            ssh_key::Signature::new(ssh_key::Algorithm::Ed25519, signature.to_vec()).unwrap(),
+        )
+        .unwrap()
+        .to_pem(ssh_key::LineEnding::LF)
+        .unwrap();
+        let signed = git
+            .commit_signed(buffer.as_str().unwrap(), &pem, None)
+            .unwrap();
+
+        let response = get(&app, format!("/repos/{RID}/commits/{signed}")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        // Aliases can change, so responses with signers are revalidated.
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-cache");
+        assert!(response.headers().contains_key(header::ETAG));
+        assert_eq!(
+            response.json().await["commit"]["signature"],
+            json!({
+                "type": "ssh",
+                "status": "valid",
+                "signer": { "id": DID, "alias": CONTRIBUTOR_ALIAS },
+                "known": true,
+            })
+        );
+
+        // Cached responses don't include aliases, they are added to each response.
+        let cached = ctx
+            .cache
+            .as_ref()
+            .unwrap()
+            .get(super::Endpoint::Commit, RID.parse().unwrap(), signed)
+            .unwrap();
+        assert_eq!(
+            cached["commit"]["signature"],
+            json!({ "type": "ssh", "status": "valid", "signer": { "id": DID } })
         );
+
+        let response = get(&app, format!("/repos/{RID}/commits/{HEAD}")).await;
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "public, max-age=604800, immutable"
+        );
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/commits?parent={signed}&verified=true"),
+        )
+        .await;
+        let page = response.json().await;
+
+        assert_eq!(page["items"].as_array().unwrap().len(), 1);
+        assert_eq!(page["items"][0]["id"], signed.to_string());
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/commits?parent={signed}&verified=false"),
+        )
+        .await;
+        let page = response.json().await;
+
+        assert_eq!(page["items"].as_array().unwrap().len(), 3);
+        assert_eq!(page["items"][0]["id"], HEAD);
+        assert_eq!(page["items"][0]["signature"], Value::Null);
     }
 
     #[tokio::test]
@@ -1088,6 +2529,7 @@ mod routes {
                   "email": "alice@radicle.xyz",
                   "time": 1673003014
                 },
+                "signature": null,
               },
               "diff": {
                 "files": [
@@ -1302,6 +2744,7 @@ mod routes {
                     "email": "alice@radicle.xyz",
                     "time": 1673003014
                   },
+                  "signature": null,
                 },
                 "name": "",
                 "path": "",
@@ -1339,6 +2782,7 @@ mod routes {
                   "email": "alice@radicle.xyz",
                   "time": 1673003014
                 },
+                "signature": null,
               },
               "name": "dir1",
               "path": "dir1",
@@ -1346,6 +2790,46 @@ mod routes {
         );
     }
 
+    #[tokio::test]
+    async fn test_repos_cache_disk() {
+        use crate::cache::{Cache, Disk, Endpoint, Key, Store as _};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("cache");
+        let mut ctx = seed(tmp.path());
+        ctx.cache = Some(Cache::with_store(
+            Disk::open(&dir, u64::MAX).unwrap(),
+            [Endpoint::Tree, Endpoint::Commit],
+        ));
+        let app = super::router(ctx);
+
+        let response = get(&app, format!("/repos/{RID}/tree/{HEAD}/dir1")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let tree = response.json().await;
+
+        let response = get(&app, format!("/repos/{RID}/commits/{HEAD}")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Blobs aren't cached, since they aren't enabled.
+        let response = get(&app, format!("/repos/{RID}/blob/{HEAD}/README")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+
+        // Cached responses survive a restart.
+        let key = Key {
+            endpoint: Endpoint::Tree,
+            rid: RID.parse().unwrap(),
+            id: format!("{HEAD}/dir1"),
+        };
+        let store = Disk::open(&dir, u64::MAX).unwrap();
+        assert_eq!(store.get(&key), Some(tree));
+
+        // Responses are evicted once the cache is full.
+        let store = Disk::open(&dir, 1).unwrap();
+        assert_eq!(store.get(&key), None);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    }
+
     #[tokio::test]
     async fn test_repos_tree_not_found() {
         let tmp = tempfile::tempdir().unwrap();
@@ -1395,28 +2879,279 @@ mod routes {
 
         assert_eq!(response.status(), StatusCode::OK);
         assert_eq!(
-            response.json().await,
+            response.json().await,
+            json!({
+                "id": "z6MknSLrJoTcukLrE435hVNQT4JUhbvWLX4kUzqkEStBU8Vi",
+                "heads": {
+                    "master": HEAD
+                },
+                "delegate": true
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repos_remotes_not_found() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(seed(tmp.path()));
+        let response = get(
+            &app,
+            format!("/repos/{RID}/remotes/z6MksFqXN3Yhqk8pTJdUGLwATkRfQvwZXPqR2qMEhbS9wzpT"),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_repos_refs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+
+        let signer = radicle::node::device::Device::mock_from_seed([0xff; 32]);
+        let other = radicle::node::device::Device::mock_from_seed([0xaa; 32]);
+        let other = *other.public_key();
+        let repo = ctx
+            .profile()
+            .storage
+            .repository(RID.parse().unwrap())
+            .unwrap();
+        let mut identity = Identity::load_mut(&repo).unwrap();
+        let doc = identity
+            .doc()
+            .clone()
+            .with_edits(|doc| doc.delegate(other.into()))
+            .unwrap();
+        identity
+            .update("Add a delegate", "", &doc, &signer)
+            .unwrap();
+        radicle::storage::WriteRepository::set_identity_head(&repo).unwrap();
+
+        // The new delegate is one commit behind on `master`, and diverges on `feature`.
+        let git = &repo.backend;
+        let head = git.find_commit(HEAD.parse().unwrap()).unwrap();
+        let sig = head.author();
+        let feature = git
+            .commit(
+                None,
+                &sig,
+                &sig,
+                "Feature\n",
+                &head.tree().unwrap(),
+                &[&head],
+            )
+            .unwrap();
+        let diverging = git
+            .commit(
+                None,
+                &sig,
+                &sig,
+                "Other feature\n",
+                &head.tree().unwrap(),
+                &[&head],
+            )
+            .unwrap();
+        let remote = signer.public_key();
+        for (remote, branch, oid) in [
+            (remote, "feature", feature),
+            (&other, "master", PARENT.parse().unwrap()),
+            (&other, "feature", diverging),
+        ] {
+            git.reference(
+                &format!("refs/namespaces/{remote}/refs/heads/{branch}"),
+                oid,
+                true,
+                "",
+            )
+            .unwrap();
+        }
+
+        let response = get(&app, format!("/repos/{RID}/refs")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let refs = response.json().await;
+        let other = Did::from(other).to_string();
+        assert_eq!(refs["threshold"], 1);
+        assert_eq!(
+            refs["branches"],
+            json!([
+                {
+                    "name": "feature",
+                    "default": false,
+                    "quorum": "diverging",
+                    "canonical": null,
+                    "delegates": [
+                        {
+                            "id": DID,
+                            "alias": CONTRIBUTOR_ALIAS,
+                            "head": feature.to_string(),
+                            "status": "unknown",
+                            "ahead": null,
+                            "behind": null,
+                        },
+                        {
+                            "id": other,
+                            "head": diverging.to_string(),
+                            "status": "unknown",
+                            "ahead": null,
+                            "behind": null,
+                        },
+                    ],
+                },
+                {
+                    "name": "master",
+                    "default": true,
+                    "quorum": "reached",
+                    "canonical": HEAD,
+                    "delegates": [
+                        {
+                            "id": DID,
+                            "alias": CONTRIBUTOR_ALIAS,
+                            "head": HEAD,
+                            "status": "agrees",
+                            "ahead": 0,
+                            "behind": 0,
+                        },
+                        {
+                            "id": other,
+                            "head": PARENT,
+                            "status": "behind",
+                            "ahead": 0,
+                            "behind": 1,
+                        },
+                    ],
+                },
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repos_tags() {
+        use radicle::crypto::signature::Signer as _;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+
+        let signer = radicle::node::device::Device::mock_from_seed([0xff; 32]);
+        let repo = ctx
+            .profile()
+            .storage
+            .repository(RID.parse().unwrap())
+            .unwrap();
+        let git = &repo.backend;
+        let remote = signer.public_key();
+
+        // An annotated tag signed with SSH, as `git tag -s` does. The signature is made over
+        // the tag with the signed message, so that it's invalid if the messages differ.
+        let tag = |name: &str, message: &str, signed: &str| {
+            let object = |message: &str| {
+                format!(
+                    "object {HEAD}\ntype commit\ntag {name}\n\
+                     tagger Alice Liddell <alice@radicle.xyz> 1673003014 +0000\n\n{message}\n"
+                )
+            };
+            let payload = object(message);
+            let data = ssh_key::SshSig::signed_data(
+                "git",
+                ssh_key::HashAlg::Sha512,
+                object(signed).as_bytes(),
+            )
+            .unwrap();
+            let sig: radicle::crypto::Signature = signer.sign(&data);
+            let pem = ssh_key::SshSig::new(
+                ssh_key::PublicKey::from(*remote).key_data().clone(),
+                "git",
+                ssh_key::HashAlg::Sha512,
+                ssh_key::Signature::new(ssh_key::Algorithm::Ed25519, sig.to_vec()).unwrap(),
+            )
+            .unwrap()
+            .to_pem(ssh_key::LineEnding::LF)
+            .unwrap();
+            let oid = git
+                .odb()
+                .unwrap()
+                .write(
+                    radicle::git::raw::ObjectType::Tag,
+                    format!("{payload}{pem}").as_bytes(),
+                )
+                .unwrap();
+            git.reference(
+                &format!("refs/namespaces/{remote}/refs/tags/{name}"),
+                oid,
+                true,
+                "",
+            )
+            .unwrap();
+        };
+        tag("v1.0", "Release v1.0", "Release v1.0");
+        tag("v1.1", "Release v1.1", "Release v1.0");
+        // Like git, only the trailing armor is taken as the signature.
+        let quoted = "Release v1.2\n\n-----BEGIN SSH SIGNATURE-----\nquoted";
+        tag("v1.2", quoted, quoted);
+        git.reference("refs/tags/v0.1", PARENT.parse().unwrap(), true, "")
+            .unwrap();
+
+        let response = get(&app, format!("/repos/{RID}/tags")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let tags = response.json().await;
+        assert_eq!(
+            tags[0],
             json!({
-                "id": "z6MknSLrJoTcukLrE435hVNQT4JUhbvWLX4kUzqkEStBU8Vi",
-                "heads": {
-                    "master": HEAD
-                },
-                "delegate": true
+                "name": "v0.1",
+                "oid": PARENT,
+                "target": PARENT,
+                "canonical": true,
+                "remotes": [],
+                "tagger": null,
+                "message": null,
+                "signature": null,
+                "archive": format!("/raw/{RID}/archive/refs/tags/v0.1"),
             })
         );
-    }
+        assert_eq!(tags[1]["name"], "v1.0");
+        assert_eq!(tags[1]["target"], HEAD);
+        assert_eq!(tags[1]["canonical"], false);
+        assert_eq!(
+            tags[1]["remotes"],
+            json!([{ "id": DID, "alias": CONTRIBUTOR_ALIAS, "delegate": true }])
+        );
+        assert_eq!(
+            tags[1]["tagger"],
+            json!({ "name": "Alice Liddell", "email": "alice@radicle.xyz", "time": 1673003014 })
+        );
+        assert_eq!(tags[1]["message"], "Release v1.0\n");
+        assert_eq!(
+            tags[1]["signature"],
+            json!({
+                "type": "ssh",
+                "status": "valid",
+                "signer": { "id": DID, "alias": CONTRIBUTOR_ALIAS },
+                "known": true,
+            })
+        );
+        assert_eq!(tags[2]["name"], "v1.1");
+        assert_eq!(tags[2]["signature"]["status"], "invalid");
+        assert_eq!(tags[3]["name"], "v1.2");
+        assert_eq!(tags[3]["message"], format!("{quoted}\n"));
+        assert_eq!(tags[3]["signature"]["status"], "valid");
 
-    #[tokio::test]
-    async fn test_repos_remotes_not_found() {
-        let tmp = tempfile::tempdir().unwrap();
-        let app = super::router(seed(tmp.path()));
-        let response = get(
-            &app,
-            format!("/repos/{RID}/remotes/z6MksFqXN3Yhqk8pTJdUGLwATkRfQvwZXPqR2qMEhbS9wzpT"),
-        )
-        .await;
+        let response = get(&app, format!("/repos/{RID}/tags/v1.0")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.json().await, json!([tags[1]]));
+
+        let response = get(&app, format!("/repos/{RID}/tags/v2.0")).await;
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let archive = tags[1]["archive"].as_str().unwrap();
+        let raw = crate::raw::router(ctx);
+        let response = get(&raw, archive.strip_prefix("/raw").unwrap()).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
     }
 
     #[tokio::test]
@@ -1448,12 +3183,57 @@ mod routes {
                     "email": "alice@radicle.xyz",
                     "time": 1673003014
                   },
+                  "signature": null,
                 },
                 "content": "Hello World!\n",
             })
         );
     }
 
+    #[tokio::test]
+    async fn test_repos_blame() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+
+        let repo = ctx
+            .profile()
+            .storage
+            .repository(RID.parse().unwrap())
+            .unwrap();
+        let git = &repo.backend;
+        let parent = git.find_commit(HEAD.parse().unwrap()).unwrap();
+        let blob = git.blob(b"Hi!\nHello World!\nBye!\n").unwrap();
+        let mut tree = git.treebuilder(Some(&parent.tree().unwrap())).unwrap();
+        tree.insert("README", blob, 0o100644).unwrap();
+        let tree = git.find_tree(tree.write().unwrap()).unwrap();
+        let sig = parent.author();
+        let head = git
+            .commit(None, &sig, &sig, "Greet and part\n", &tree, &[&parent])
+            .unwrap();
+
+        let response = get(&app, format!("/repos/{RID}/blame/{head}/README")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.json().await;
+        assert_eq!(body["path"], "README");
+        assert_eq!(body["blame"][0]["commit"]["id"], head.to_string());
+        assert_eq!(body["blame"][0]["commit"]["summary"], "Greet and part");
+        assert_eq!(
+            body["blame"][0]["ranges"],
+            json!([{ "start": 1, "end": 1 }, { "start": 3, "end": 3 }])
+        );
+        assert_eq!(body["blame"][1]["commit"]["id"], HEAD);
+        assert_eq!(
+            body["blame"][1]["ranges"],
+            json!([{ "start": 2, "end": 2 }])
+        );
+
+        let response = get(&app, format!("/repos/{RID}/blame/{head}/unknown")).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
     #[tokio::test]
     async fn test_repos_blob_not_found() {
         let tmp = tempfile::tempdir().unwrap();
@@ -1490,12 +3270,98 @@ mod routes {
                     "email": "alice@radicle.xyz",
                     "time": 1673001014
                   },
+                  "signature": null,
                 },
                 "content": "Hello World!\n"
             })
         );
     }
 
+    #[tokio::test]
+    async fn test_repos_search_code() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(seed(tmp.path()));
+        let response = get(
+            &app,
+            format!("/repos/{RID}/search/code?q=World&sha={HEAD}&context=1"),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.json().await,
+            json!({
+              "sha": HEAD,
+              "files": [
+                {
+                  "path": "README",
+                  "matches": [
+                    {
+                      "line": 1,
+                      "content": "Hello World!",
+                      "before": [],
+                      "after": [],
+                    },
+                  ],
+                },
+                {
+                  "path": "dir1/README",
+                  "matches": [
+                    {
+                      "line": 1,
+                      "content": "Hello World from dir1!",
+                      "before": [],
+                      "after": [],
+                    },
+                  ],
+                },
+              ],
+              "truncated": false,
+            })
+        );
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/search/code?q=from%20dir%5Cd&regex=true&path=dir1"),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.json().await,
+            json!({
+              "sha": HEAD,
+              "files": [
+                {
+                  "path": "dir1/README",
+                  "matches": [
+                    {
+                      "line": 1,
+                      "content": "Hello World from dir1!",
+                      "before": [],
+                      "after": [],
+                    },
+                  ],
+                },
+              ],
+              "truncated": false,
+            })
+        );
+
+        let response = get(&app, format!("/repos/{RID}/search/code?q=(&regex=true")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = get(&app, format!("/repos/{RID}/search/code?q=World&path=nope")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/search/code?q=World&path=README"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[tokio::test]
     async fn test_repos_diff() {
         let tmp = tempfile::tempdir().unwrap();
@@ -1575,6 +3441,7 @@ mod routes {
                       "email": "alice@radicle.xyz",
                       "time": 1673003014,
                     },
+                    "signature": null,
                   },
                   {
                     "id": PARENT,
@@ -1591,7 +3458,8 @@ mod routes {
                       "name": "Alice Liddell",
                       "email": "alice@radicle.xyz",
                       "time": 1673002014,
-                    }
+                    },
+                    "signature": null,
                   }
                 ],
             })
@@ -1607,8 +3475,64 @@ mod routes {
         assert_eq!(response.status(), StatusCode::OK);
         assert_eq!(
             response.json().await,
-            json!([
-              {
+            json!({
+              "items": [
+                {
+                  "id": ISSUE_ID,
+                  "author": {
+                    "id": DID,
+                    "alias": CONTRIBUTOR_ALIAS
+                  },
+                  "title": "Issue #1",
+                  "state": {
+                    "status": "open"
+                  },
+                  "assignees": [],
+                  "discussion": [
+                    {
+                      "id": ISSUE_ID,
+                      "author": {
+                        "id": DID,
+                        "alias": CONTRIBUTOR_ALIAS
+                      },
+                      "body": "Change 'hello world' to 'hello everyone'",
+                      "edits": [
+                        {
+                          "author": {
+                            "id": DID,
+                            "alias": CONTRIBUTOR_ALIAS
+                          },
+                          "body": "Change 'hello world' to 'hello everyone'",
+                          "timestamp": TIMESTAMP,
+                          "embeds": [],
+                        },
+                      ],
+                      "embeds": [],
+                      "reactions": [],
+                      "timestamp": TIMESTAMP,
+                      "replyTo": null,
+                      "resolved": false,
+                    }
+                  ],
+                  "labels": []
+                }
+              ],
+              "total": 1,
+              "nextCursor": null,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repos_issue() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(seed(tmp.path()));
+        let response = get(&app, format!("/repos/{RID}/issues/{ISSUE_ID}")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.json().await,
+            json!({
                 "id": ISSUE_ID,
                 "author": {
                   "id": DID,
@@ -1646,119 +3570,421 @@ mod routes {
                   }
                 ],
                 "labels": []
-              }
-            ])
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repos_etag() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone()).layer(axum::middleware::from_fn(
+            crate::axum_extra::conditional_middleware,
+        ));
+        let etag = |response: &Response| response.headers()["ETag"].to_str().unwrap().to_owned();
+
+        for path in [
+            format!("/repos/{RID}"),
+            format!("/repos/{RID}/remotes"),
+            format!("/repos/{RID}/tree/{HEAD}/"),
+        ] {
+            let response = get(&app, &path).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let tag = etag(&response);
+
+            let response = get_if_none_match(&app, &path, &tag).await;
+            assert_eq!(response.status(), StatusCode::NOT_MODIFIED, "{path}");
+            assert_eq!(etag(&response), tag);
+            assert!(response.body().await.is_empty());
+
+            let response = get_if_none_match(&app, &path, "\"other\"").await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let path = format!("/repos/{RID}/issues");
+        let tag = etag(&get(&app, &path).await);
+        let response = get_if_none_match(&app, &path, &tag).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        // Opening an issue changes the ETag of the issue list.
+        let body = serde_json::to_vec(&json!({
+            "title": "Issue #2",
+            "description": "Change 'hello world' to 'hello radicle'",
+        }))
+        .unwrap();
+        let response = post(
+            &app,
+            &path,
+            Some(Body::from(body)),
+            Some(create_session(&ctx)),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let response = get_if_none_match(&app, &path, &tag).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(etag(&response), tag);
+
+        // Renaming an author changes the ETags of the responses rendering its alias.
+        let paths = [
+            format!("/repos/{RID}"),
+            format!("/repos/{RID}/issues"),
+            format!("/repos/{RID}/patches/{PATCH_ID}"),
+        ];
+        let mut tags = Vec::new();
+        for path in &paths {
+            tags.push(etag(&get(&app, path).await));
+        }
+        ctx.profile()
+            .home
+            .policies_mut()
+            .unwrap()
+            .follow(
+                &ctx.profile().public_key,
+                Some(&radicle::node::Alias::new("alice")),
+            )
+            .unwrap();
+
+        for (path, tag) in paths.iter().zip(tags) {
+            let response = get_if_none_match(&app, path, &tag).await;
+            assert_eq!(response.status(), StatusCode::OK, "{path}");
+            assert_ne!(etag(&response), tag);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repos_issues_create() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let body = serde_json::to_vec(&json!({
+            "title": "Issue #2",
+            "description": "Change 'hello world' to 'hello radicle'",
+            "labels": ["bug"],
+        }))
+        .unwrap();
+
+        let response = post(
+            &app,
+            format!("/repos/{RID}/issues"),
+            Some(Body::from(body.clone())),
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let token = create_session(&ctx);
+        let response = post(
+            &app,
+            format!("/repos/{RID}/issues"),
+            Some(Body::from(body)),
+            Some(token),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let json = response.json().await;
+        assert_eq!(json["success"], json!(true));
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/issues/{}", json["id"].as_str().unwrap()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let issue = response.json().await;
+        assert_eq!(issue["title"], json!("Issue #2"));
+        assert_eq!(issue["labels"], json!(["bug"]));
+        assert_eq!(
+            issue["discussion"][0]["body"],
+            json!("Change 'hello world' to 'hello radicle'")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repos_issues_filters() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let token = create_session(&ctx);
+        let body = serde_json::to_vec(&json!({
+            "title": "Issue #2",
+            "description": format!("Could {DID} have a look?"),
+            "labels": ["bug"],
+        }))
+        .unwrap();
+        let response = post(
+            &app,
+            format!("/repos/{RID}/issues"),
+            Some(Body::from(body)),
+            Some(token.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let titles = |page: Value| {
+            page["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|i| i["title"].as_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        let body =
+            serde_json::to_vec(&json!({ "type": "comment", "body": "Looking into it" })).unwrap();
+        let response = patch(
+            &app,
+            format!("/repos/{RID}/issues/{ISSUE_ID}"),
+            Some(Body::from(body)),
+            Some(token.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = get(&app, format!("/repos/{RID}/issues?sort=comments")).await;
+        assert_eq!(titles(response.json().await), ["Issue #1", "Issue #2"]);
+
+        let response = get(&app, format!("/repos/{RID}/issues?sort=comments&order=asc")).await;
+        assert_eq!(titles(response.json().await), ["Issue #2", "Issue #1"]);
+
+        let response = get(&app, format!("/repos/{RID}/issues?labels=bug")).await;
+        assert_eq!(titles(response.json().await), ["Issue #2"]);
+
+        let response = get(&app, format!("/repos/{RID}/issues?labels=bug,wontfix")).await;
+        assert_eq!(response.json().await["total"], 0);
+
+        let response = get(&app, format!("/repos/{RID}/issues?author={DID}&q=EVERYONE")).await;
+        assert_eq!(titles(response.json().await), ["Issue #1"]);
+
+        let response = get(&app, format!("/repos/{RID}/issues?createdUntil=0")).await;
+        assert_eq!(response.json().await["total"], 0);
+
+        let response = get(&app, format!("/repos/{RID}/issues?mentions=me")).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = get_auth(&app, format!("/repos/{RID}/issues?mentions=me"), token).await;
+        assert_eq!(titles(response.json().await), ["Issue #2"]);
+
+        let response = get(&app, format!("/repos/{RID}/issues?sort=votes")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_repos_issues_update() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let token = create_session(&ctx);
+
+        let body = serde_json::to_vec(&json!({
+            "type": "comment",
+            "body": "This is a reply",
+            "replyTo": ISSUE_ID,
+        }))
+        .unwrap();
+        let response = patch(
+            &app,
+            format!("/repos/{RID}/issues/{ISSUE_ID}"),
+            Some(Body::from(body)),
+            Some(token.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = serde_json::to_vec(&json!({
+            "type": "assign",
+            "assignees": [DID],
+        }))
+        .unwrap();
+        let response = patch(
+            &app,
+            format!("/repos/{RID}/issues/{ISSUE_ID}"),
+            Some(Body::from(body)),
+            Some(token.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = serde_json::to_vec(&json!({
+            "type": "lifecycle",
+            "state": { "status": "closed", "reason": "solved" },
+        }))
+        .unwrap();
+        let response = patch(
+            &app,
+            format!("/repos/{RID}/issues/{ISSUE_ID}"),
+            Some(Body::from(body)),
+            Some(token),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = get(&app, format!("/repos/{RID}/issues/{ISSUE_ID}")).await;
+        let issue = response.json().await;
+
+        assert_eq!(
+            issue["state"],
+            json!({ "status": "closed", "reason": "solved" })
+        );
+        assert_eq!(
+            issue["assignees"],
+            json!([{ "id": DID, "alias": CONTRIBUTOR_ALIAS }])
         );
+        assert_eq!(issue["discussion"][1]["body"], json!("This is a reply"));
+        assert_eq!(issue["discussion"][1]["replyTo"], json!(ISSUE_ID));
+    }
+
+    #[tokio::test]
+    async fn test_repos_issues_update_forbidden() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let signer = radicle::node::device::Device::mock_from_seed([0xaa; 32]);
+        let token = create_session_for(&ctx, *signer.public_key(), "other");
+        let body = serde_json::to_vec(&json!({ "type": "edit", "title": "New title" })).unwrap();
+
+        let response = patch(
+            &app,
+            format!("/repos/{RID}/issues/{ISSUE_ID}"),
+            Some(Body::from(body)),
+            Some(token),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
     }
 
     #[tokio::test]
-    async fn test_repos_issue() {
+    async fn test_repos_patches_root() {
         let tmp = tempfile::tempdir().unwrap();
         let app = super::router(seed(tmp.path()));
-        let response = get(&app, format!("/repos/{RID}/issues/{ISSUE_ID}")).await;
+        let response = get(&app, format!("/repos/{RID}/patches")).await;
 
         assert_eq!(response.status(), StatusCode::OK);
         assert_eq!(
             response.json().await,
             json!({
-                "id": ISSUE_ID,
-                "author": {
-                  "id": DID,
-                  "alias": CONTRIBUTOR_ALIAS
-                },
-                "title": "Issue #1",
-                "state": {
-                  "status": "open"
-                },
-                "assignees": [],
-                "discussion": [
+              "items": [
                   {
-                    "id": ISSUE_ID,
-                    "author": {
-                      "id": DID,
-                      "alias": CONTRIBUTOR_ALIAS
-                    },
-                    "body": "Change 'hello world' to 'hello everyone'",
-                    "edits": [
-                      {
-                        "author": {
+                      "id": PATCH_ID,
+                      "author": {
                           "id": DID,
-                          "alias": CONTRIBUTOR_ALIAS
-                        },
-                        "body": "Change 'hello world' to 'hello everyone'",
-                        "timestamp": TIMESTAMP,
-                        "embeds": [],
+                          "alias": CONTRIBUTOR_ALIAS,
                       },
-                    ],
-                    "embeds": [],
-                    "reactions": [],
-                    "timestamp": TIMESTAMP,
-                    "replyTo": null,
-                    "resolved": false,
-                  }
-                ],
-                "labels": []
+                      "title": "A new `hello world`",
+                      "state": {
+                          "status": "open",
+                      },
+                      "target": "delegates",
+                      "labels": [],
+                      "merges": [],
+                      "assignees": [],
+                      "revisions": [
+                          {
+                              "id": PATCH_ID,
+                              "author": {
+                                  "id": DID,
+                                  "alias": CONTRIBUTOR_ALIAS,
+                              },
+                              "description": "change `hello world` in README to something else",
+                              "edits": [
+                                  {
+                                      "author": {
+                                          "id": DID,
+                                          "alias": CONTRIBUTOR_ALIAS,
+                                      },
+                                      "body": "change `hello world` in README to something else",
+                                      "timestamp": TIMESTAMP,
+                                      "embeds": [],
+                                  },
+                              ],
+                              "reactions": [],
+                              "base": "ee8d6a29304623a78ebfa5eeed5af674d0e58f83",
+                              "oid": "e8c676b9e3b42308dc9d218b70faa5408f8e58ca",
+                              "refs": [
+                                  "refs/heads/master",
+                              ],
+                              "discussions": [],
+                              "timestamp": TIMESTAMP,
+                              "reviews": [],
+                          },
+                      ],
+                  },
+                  ],
+              "total": 1,
+              "nextCursor": null,
             })
         );
     }
 
     #[tokio::test]
-    async fn test_repos_patches_root() {
+    async fn test_repos_patches_summary() {
         let tmp = tempfile::tempdir().unwrap();
         let app = super::router(seed(tmp.path()));
-        let response = get(&app, format!("/repos/{RID}/patches")).await;
+        let response = get(&app, format!("/repos/{RID}/patches?view=summary")).await;
 
         assert_eq!(response.status(), StatusCode::OK);
         assert_eq!(
-            response.json().await,
+            response.json().await["items"],
             json!([
-                {
-                    "id": PATCH_ID,
-                    "author": {
-                        "id": DID,
-                        "alias": CONTRIBUTOR_ALIAS,
-                    },
-                    "title": "A new `hello world`",
-                    "state": {
-                        "status": "open",
-                    },
-                    "target": "delegates",
-                    "labels": [],
-                    "merges": [],
-                    "assignees": [],
-                    "revisions": [
-                        {
-                            "id": PATCH_ID,
-                            "author": {
-                                "id": DID,
-                                "alias": CONTRIBUTOR_ALIAS,
-                            },
-                            "description": "change `hello world` in README to something else",
-                            "edits": [
-                                {
-                                    "author": {
-                                        "id": DID,
-                                        "alias": CONTRIBUTOR_ALIAS,
-                                    },
-                                    "body": "change `hello world` in README to something else",
-                                    "timestamp": TIMESTAMP,
-                                    "embeds": [],
-                                },
-                            ],
-                            "reactions": [],
-                            "base": "ee8d6a29304623a78ebfa5eeed5af674d0e58f83",
-                            "oid": "e8c676b9e3b42308dc9d218b70faa5408f8e58ca",
-                            "refs": [
-                                "refs/heads/master",
-                            ],
-                            "discussions": [],
-                            "timestamp": TIMESTAMP,
-                            "reviews": [],
-                        },
-                    ],
+              {
+                "id": PATCH_ID,
+                "author": {
+                  "id": DID,
+                  "alias": CONTRIBUTOR_ALIAS,
                 },
-                ]
-            )
+                "title": "A new `hello world`",
+                "state": {
+                  "status": "open",
+                },
+                "target": "delegates",
+                "labels": [],
+                "assignees": [],
+                "latestRevision": {
+                  "id": PATCH_ID,
+                  "author": {
+                    "id": DID,
+                    "alias": CONTRIBUTOR_ALIAS,
+                  },
+                  "description": "change `hello world` in README to something else",
+                  "base": "ee8d6a29304623a78ebfa5eeed5af674d0e58f83",
+                  "oid": "e8c676b9e3b42308dc9d218b70faa5408f8e58ca",
+                  "timestamp": TIMESTAMP,
+                },
+                "counts": {
+                  "revisions": 1,
+                  "comments": 0,
+                  "reviews": 0,
+                },
+              },
+            ])
+        );
+
+        let response = get(&app, format!("/repos/{RID}/issues?view=summary")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.json().await["items"],
+            json!([
+              {
+                "id": ISSUE_ID,
+                "author": {
+                  "id": DID,
+                  "alias": CONTRIBUTOR_ALIAS,
+                },
+                "title": "Issue #1",
+                "state": {
+                  "status": "open",
+                },
+                "assignees": [],
+                "labels": [],
+                "timestamp": TIMESTAMP,
+                "counts": {
+                  "comments": 0,
+                },
+              },
+            ])
         );
     }
 
@@ -1819,6 +4045,326 @@ mod routes {
         );
     }
 
+    #[tokio::test]
+    async fn test_repos_patches_filters() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let token = create_session(&ctx);
+        let total = |path: String| {
+            let app = app.clone();
+            async move {
+                let response = get(&app, format!("/repos/{RID}/patches?{path}")).await;
+                assert_eq!(response.status(), StatusCode::OK);
+                response.json().await["total"].clone()
+            }
+        };
+
+        assert_eq!(total(String::from("target=master")).await, 1);
+        assert_eq!(total(String::from("target=refs/heads/master")).await, 1);
+        assert_eq!(total(String::from("target=feature")).await, 0);
+        assert_eq!(total(String::from("verdict=pending")).await, 1);
+        assert_eq!(total(String::from("verdict=accepted")).await, 0);
+        assert_eq!(total(format!("reviewer={DID}")).await, 0);
+        assert_eq!(total(format!("reviewer={DID}&verdict=pending")).await, 1);
+        assert_eq!(total(String::from("unresolved=false")).await, 1);
+        assert_eq!(total(format!("base={PARENT}&head={HEAD}")).await, 1);
+        assert_eq!(total(format!("head={INITIAL_COMMIT}")).await, 0);
+        assert_eq!(total(String::from("sort=revision&order=asc")).await, 1);
+
+        for action in [
+            json!({
+                "type": "review",
+                "revision": PATCH_ID,
+                "summary": "Looks good",
+                "verdict": "accept",
+            }),
+            json!({
+                "type": "revision.comment",
+                "revision": PATCH_ID,
+                "body": "What about this line?",
+            }),
+        ] {
+            let response = patch(
+                &app,
+                format!("/repos/{RID}/patches/{PATCH_ID}"),
+                Some(Body::from(serde_json::to_vec(&action).unwrap())),
+                Some(token.clone()),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        assert_eq!(total(String::from("verdict=accepted")).await, 1);
+        assert_eq!(total(String::from("verdict=pending")).await, 0);
+        assert_eq!(total(format!("reviewer={DID}&verdict=accepted")).await, 1);
+        assert_eq!(total(format!("reviewer={DID}&verdict=rejected")).await, 0);
+        assert_eq!(total(String::from("unresolved=true")).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_repos_patches_interdiff() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+
+        // Rework the head commit, and include its parent in the patch.
+        let signer = radicle::node::device::Device::mock_from_seed([0xff; 32]);
+        let repo = ctx
+            .profile()
+            .storage
+            .repository(RID.parse().unwrap())
+            .unwrap();
+        let git = &repo.backend;
+        let parent = git.find_commit(PARENT.parse().unwrap()).unwrap();
+        let blob = git.blob(b"Hello everyone from dir1!\n").unwrap();
+        let mut dir = git.treebuilder(None).unwrap();
+        dir.insert("README", blob, 0o100644).unwrap();
+        let dir = dir.write().unwrap();
+        let tree = git
+            .find_commit(HEAD.parse().unwrap())
+            .unwrap()
+            .tree()
+            .unwrap();
+        let mut tree = git.treebuilder(Some(&tree)).unwrap();
+        tree.insert("dir1", dir, 0o040000).unwrap();
+        let tree = git.find_tree(tree.write().unwrap()).unwrap();
+        let sig = parent.author();
+        let head = git
+            .commit(None, &sig, &sig, "Add another folder\n", &tree, &[&parent])
+            .unwrap();
+        let mut patches = ctx.profile().patches_mut(&repo).unwrap();
+        let mut patch = patches.get_mut(&PATCH_ID.parse().unwrap()).unwrap();
+        let revision = patch
+            .update(
+                "Reword the folder README",
+                INITIAL_COMMIT.parse::<radicle::git::Oid>().unwrap(),
+                head,
+                &signer,
+            )
+            .unwrap();
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/patches/{PATCH_ID}/revisions/{PATCH_ID}/interdiff/{revision}"),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.json().await;
+        assert_eq!(body["old"]["head"], HEAD);
+        assert_eq!(body["new"]["head"], head.to_string());
+
+        let commits = body["commits"].as_array().unwrap();
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0]["status"], "added");
+        assert_eq!(commits[0]["old"], json!(null));
+        assert_eq!(commits[0]["new"]["id"], PARENT);
+        assert_eq!(
+            commits[0]["diff"]["files"][0]["path"],
+            json!("CONTRIBUTING")
+        );
+        assert_eq!(commits[1]["status"], "modified");
+        assert_eq!(commits[1]["old"]["id"], HEAD);
+        assert_eq!(commits[1]["new"]["id"], head.to_string());
+
+        let files = commits[1]["diff"]["files"].as_array().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0]["path"], "dir1/README");
+        let lines = files[0]["diff"]["hunks"][0]["lines"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|l| l["type"] != "context")
+            .map(|l| (l["type"].as_str().unwrap(), l["line"].as_str().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                ("deletion", "+Hello World from dir1!\n"),
+                ("addition", "+Hello everyone from dir1!\n"),
+            ]
+        );
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/patches/{PATCH_ID}/revisions/{revision}/interdiff/{PATCH_ID}"),
+        )
+        .await;
+
+        let body = response.json().await;
+        assert_eq!(body["commits"][0]["status"], "removed");
+        assert_eq!(body["commits"][0]["old"]["id"], PARENT);
+        assert_eq!(body["commits"][1]["status"], "modified");
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/patches/{PATCH_ID}/revisions/{PATCH_ID}/interdiff/{PATCH_ID}"),
+        )
+        .await;
+
+        let body = response.json().await;
+        assert_eq!(body["commits"][0]["status"], "unchanged");
+        assert_eq!(body["commits"][0]["diff"], json!(null));
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/patches/{PATCH_ID}/revisions/{HEAD}/interdiff/{PATCH_ID}"),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_repos_patches_update() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let token = create_session(&ctx);
+        let location = json!({
+            "commit": HEAD,
+            "path": "README",
+            "new": { "type": "lines", "range": { "start": 0, "end": 1 } },
+        });
+
+        let body = serde_json::to_vec(&json!({
+            "type": "revision.comment",
+            "revision": PATCH_ID,
+            "body": "What about this line?",
+            "location": location,
+        }))
+        .unwrap();
+        let response = patch(
+            &app,
+            format!("/repos/{RID}/patches/{PATCH_ID}"),
+            Some(Body::from(body)),
+            Some(token.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = serde_json::to_vec(&json!({
+            "type": "review",
+            "revision": PATCH_ID,
+            "summary": "Looks good",
+            "verdict": "accept",
+        }))
+        .unwrap();
+        let response = patch(
+            &app,
+            format!("/repos/{RID}/patches/{PATCH_ID}"),
+            Some(Body::from(body)),
+            Some(token.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let review = response.json().await["id"].clone();
+
+        let body = serde_json::to_vec(&json!({
+            "type": "review.comment",
+            "review": review,
+            "body": "Nit: typo",
+            "location": location,
+        }))
+        .unwrap();
+        let response = patch(
+            &app,
+            format!("/repos/{RID}/patches/{PATCH_ID}"),
+            Some(Body::from(body)),
+            Some(token.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = serde_json::to_vec(&json!({
+            "type": "lifecycle",
+            "state": { "status": "archived" },
+        }))
+        .unwrap();
+        let response = patch(
+            &app,
+            format!("/repos/{RID}/patches/{PATCH_ID}"),
+            Some(Body::from(body)),
+            Some(token),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = get(&app, format!("/repos/{RID}/patches/{PATCH_ID}")).await;
+        let patch = response.json().await;
+        let revision = &patch["revisions"][0];
+
+        assert_eq!(patch["state"], json!({ "status": "archived" }));
+        assert_eq!(
+            revision["discussions"][0]["body"],
+            json!("What about this line?")
+        );
+        assert_eq!(revision["discussions"][0]["location"], location);
+        assert_eq!(revision["reviews"][0]["id"], review);
+        assert_eq!(revision["reviews"][0]["verdict"], json!("accept"));
+        assert_eq!(revision["reviews"][0]["summary"], json!("Looks good"));
+        assert_eq!(
+            revision["reviews"][0]["comments"][0]["body"],
+            json!("Nit: typo")
+        );
+        assert_eq!(revision["reviews"][0]["comments"][0]["location"], location);
+    }
+
+    #[tokio::test]
+    async fn test_repos_patches_update_unsupported() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let token = create_session(&ctx);
+
+        for action in [
+            json!({ "type": "merge", "revision": PATCH_ID, "commit": HEAD }),
+            json!({
+                "type": "revision",
+                "description": "",
+                "base": PARENT,
+                "oid": HEAD,
+            }),
+            json!({ "type": "edit", "title": "New title", "target": "delegates" }),
+        ] {
+            let response = patch(
+                &app,
+                format!("/repos/{RID}/patches/{PATCH_ID}"),
+                Some(Body::from(serde_json::to_vec(&action).unwrap())),
+                Some(token.clone()),
+            )
+            .await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{action}");
+        }
+
+        let response = get(&app, format!("/repos/{RID}/patches/{PATCH_ID}")).await;
+        let patch = response.json().await;
+
+        assert_eq!(patch["state"], json!({ "status": "open" }));
+        assert_eq!(patch["revisions"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_repos_patches_update_unauthorized() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(seed(tmp.path()));
+        let body = serde_json::to_vec(&json!({
+            "type": "lifecycle",
+            "state": { "status": "draft" },
+        }))
+        .unwrap();
+
+        let response = patch(
+            &app,
+            format!("/repos/{RID}/patches/{PATCH_ID}"),
+            Some(Body::from(body)),
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
     #[tokio::test]
     async fn test_repos_private() {
         let tmp = tempfile::tempdir().unwrap();
@@ -1846,4 +4392,104 @@ mod routes {
         let response = get(&app, format!("/repos/{RID_PRIVATE}/remotes")).await;
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
+
+    #[tokio::test]
+    async fn test_repos_private_session() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.to_owned());
+        let stranger = radicle::node::device::Device::mock_from_seed([0xaa; 32]);
+        let stranger = create_session_for(&ctx, *stranger.public_key(), "stranger");
+        let delegate = create_session(&ctx);
+
+        for path in ["", "/patches", "/issues", "/commits", "/remotes"] {
+            let path = format!("/repos/{RID_PRIVATE}{path}");
+
+            let response = get_auth(&app, &path, stranger.clone()).await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND, "{path}");
+
+            let response = get_auth(&app, &path, delegate.clone()).await;
+            assert_eq!(response.status(), StatusCode::OK, "{path}");
+        }
+
+        // Private repositories are listed to the sessions they are visible to.
+        ctx.profile()
+            .policies_mut()
+            .unwrap()
+            .set_seed_policy(
+                &RID_PRIVATE.parse().unwrap(),
+                radicle::node::policy::Policy::Allow,
+            )
+            .unwrap();
+        let listed = |page: Value| {
+            page["items"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|repo| repo["rid"] == RID_PRIVATE)
+        };
+        let response = get(&app, "/repos?show=all").await;
+        assert!(!listed(response.json().await));
+
+        let response = get_auth(&app, "/repos?show=all", stranger).await;
+        assert!(!listed(response.json().await));
+
+        let response = get_auth(&app, "/repos?show=all", delegate.clone()).await;
+        assert!(listed(response.json().await));
+
+        let response = get_auth(&app, format!("/repos/{RID_PRIVATE}"), delegate).await;
+        assert_eq!(
+            response.json().await["visibility"],
+            json!({ "type": "private" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repos_cobs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(seed(tmp.path()));
+
+        let response = get(&app, format!("/repos/{RID}/cobs/xyz.radicle.issue")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let page = response.json().await;
+        assert_eq!(page["total"], 1);
+        assert_eq!(page["items"][0]["id"], ISSUE_ID);
+        assert_eq!(page["items"][0]["typeName"], "xyz.radicle.issue");
+        assert_eq!(page["items"][0]["author"]["id"], DID);
+        assert_eq!(page["items"][0]["timestamp"], TIMESTAMP);
+
+        let response = get(
+            &app,
+            format!("/repos/{RID}/cobs/xyz.radicle.issue/{ISSUE_ID}"),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let cob = response.json().await;
+        assert_eq!(cob["id"], ISSUE_ID);
+        assert_eq!(cob["manifest"]["typeName"], "xyz.radicle.issue");
+        let changes = cob["changes"].as_array().unwrap();
+        assert_eq!(changes[0]["id"], ISSUE_ID);
+        assert_eq!(changes[0]["author"]["id"], DID);
+        assert_eq!(changes[0]["parents"], json!([]));
+        assert_eq!(changes[0]["actions"][0]["type"], "comment");
+        assert_eq!(
+            changes[0]["actions"][0]["body"],
+            "Change 'hello world' to 'hello everyone'"
+        );
+
+        let response = get(&app, format!("/repos/{RID}/cobs/xyz.example.todo")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.json().await["total"], 0);
+
+        let response = get(&app, format!("/repos/{RID}/cobs/xyz.radicle.issue/{HEAD}")).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = get(&app, format!("/repos/{RID}/cobs/not%20a%20type")).await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 }
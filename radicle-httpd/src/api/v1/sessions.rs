@@ -0,0 +1,234 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::{get, put};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::json;
+
+use radicle::crypto::{PublicKey, Signature};
+
+use crate::api::auth::Session;
+use crate::api::error::Error;
+use crate::api::Context;
+use crate::axum_extra::Path;
+
+pub fn router(ctx: Context) -> Router {
+    Router::new()
+        .route(
+            "/sessions",
+            get(sessions_handler).post(session_create_handler),
+        )
+        .route(
+            "/sessions/:id",
+            put(session_authorize_handler).delete(session_revoke_handler),
+        )
+        .with_state(ctx)
+}
+
+/// Signed session challenge.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthorizeSession {
+    /// Key the nonce was signed with.
+    public_key: PublicKey,
+    /// Signature over the session nonce.
+    signature: Signature,
+}
+
+/// List the sessions of the authenticated key.
+/// `GET /sessions`
+async fn sessions_handler(State(ctx): State<Context>, session: Session) -> impl IntoResponse {
+    let sessions = ctx.sessions.list(&session.public_key)?;
+
+    Ok::<_, Error>(Json(sessions))
+}
+
+/// Create a session challenge. The returned nonce has to be signed to authorize the session.
+/// `POST /sessions`
+async fn session_create_handler(State(ctx): State<Context>) -> impl IntoResponse {
+    let now = chrono::Utc::now().timestamp();
+    let challenge = ctx.sessions.challenge(now)?.ok_or(Error::TooManyRequests)?;
+
+    Ok::<_, Error>((StatusCode::CREATED, Json(challenge)))
+}
+
+/// Authorize a session with a signature over its nonce, returning the session's bearer token.
+/// `PUT /sessions/:id`
+async fn session_authorize_handler(
+    State(ctx): State<Context>,
+    Path(id): Path<String>,
+    Json(body): Json<AuthorizeSession>,
+) -> impl IntoResponse {
+    let now = chrono::Utc::now().timestamp();
+    let challenge = ctx
+        .sessions
+        .get_challenge(&id, now)?
+        .ok_or(Error::NotFound)?;
+
+    if body
+        .public_key
+        .verify(challenge.nonce.as_bytes(), &body.signature)
+        .is_err()
+    {
+        return Err(Error::Unauthorized);
+    }
+    let (session, token) = ctx
+        .sessions
+        .authorize(&id, body.public_key, now)?
+        .ok_or(Error::NotFound)?;
+
+    Ok::<_, Error>(Json(json!({
+        "id": session.id,
+        "token": token,
+        "publicKey": session.public_key,
+        "issuedAt": session.issued_at,
+        "expiresAt": session.expires_at,
+    })))
+}
+
+/// Revoke a session of the authenticated key.
+/// `DELETE /sessions/:id`
+async fn session_revoke_handler(
+    State(ctx): State<Context>,
+    Path(id): Path<String>,
+    session: Session,
+) -> impl IntoResponse {
+    if !ctx.sessions.revoke(&id, &session.public_key)? {
+        return Err(Error::NotFound);
+    }
+    Ok::<_, Error>(Json(json!({ "success": true })))
+}
+
+#[cfg(test)]
+mod routes {
+    use axum::body::Body;
+    use axum::http::StatusCode;
+    use pretty_assertions::assert_eq;
+    use radicle::crypto::signature::Signer;
+    use radicle::crypto::Signature;
+    use serde_json::json;
+
+    use crate::test::*;
+
+    fn sign(
+        signer: &radicle::node::device::Device<impl Signer<Signature>>,
+        msg: &[u8],
+    ) -> Signature {
+        signer.sign(msg)
+    }
+
+    #[tokio::test]
+    async fn test_sessions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let signer = radicle::node::device::Device::mock_from_seed([0xaa; 32]);
+
+        let response = post(&app, "/sessions", None, None).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let challenge = response.json().await;
+        let id = challenge["id"].as_str().unwrap().to_owned();
+        let nonce = challenge["nonce"].as_str().unwrap().to_owned();
+
+        // A signature over anything other than the nonce is rejected.
+        let body = serde_json::to_vec(&json!({
+            "publicKey": signer.public_key(),
+            "signature": sign(&signer, b"nonce"),
+        }))
+        .unwrap();
+        let response = put(
+            &app,
+            format!("/sessions/{id}"),
+            Some(Body::from(body)),
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let body = serde_json::to_vec(&json!({
+            "publicKey": signer.public_key(),
+            "signature": sign(&signer, nonce.as_bytes()),
+        }))
+        .unwrap();
+        let response = put(
+            &app,
+            format!("/sessions/{id}"),
+            Some(Body::from(body)),
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let session = response.json().await;
+        let token = session["token"].as_str().unwrap().to_owned();
+        assert_eq!(session["id"], json!(id));
+        assert_eq!(session["publicKey"], json!(signer.public_key()));
+
+        // A session can only be authorized once.
+        let body = serde_json::to_vec(&json!({
+            "publicKey": signer.public_key(),
+            "signature": sign(&signer, nonce.as_bytes()),
+        }))
+        .unwrap();
+        let response = put(
+            &app,
+            format!("/sessions/{id}"),
+            Some(Body::from(body)),
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = get_auth(&app, "/sessions", token.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.json().await,
+            json!([{
+                "id": id,
+                "publicKey": signer.public_key(),
+                "issuedAt": session["issuedAt"],
+                "expiresAt": session["expiresAt"],
+            }])
+        );
+
+        // Sessions of other keys can't be revoked.
+        let other = create_session(&ctx);
+        let response = delete(&app, format!("/sessions/{id}"), Some(other)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = delete(&app, format!("/sessions/{id}"), Some(token.clone())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = get_auth(&app, "/sessions", token).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_challenge_limit() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(seed(tmp.path()));
+
+        for _ in 0..1024 {
+            let response = post(&app, "/sessions", None, None).await;
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+        let response = post(&app, "/sessions", None, None).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_persisted() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let token = create_session(&ctx);
+
+        // Re-open the sessions database, as on restart.
+        let sessions =
+            crate::api::auth::Sessions::open(tmp.path().join("radicle/httpd/sessions.db")).unwrap();
+        let session = sessions.get(&token).unwrap().unwrap();
+
+        assert_eq!(session.public_key, ctx.profile().public_key);
+    }
+}
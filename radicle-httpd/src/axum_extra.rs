@@ -1,13 +1,18 @@
+use axum::body::Body;
 use axum::extract::path::ErrorKind;
 use axum::extract::rejection::{PathRejection, QueryRejection};
 use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
-use axum::http::{header, StatusCode};
-use axum::response::IntoResponse;
+use axum::http::{header, HeaderValue, Method, Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
 use axum::{async_trait, Json};
 
+use base64::prelude::BASE64_URL_SAFE_NO_PAD;
+use base64::Engine;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use sha2::{Digest, Sha256};
 
 pub struct Path<T>(pub T);
 
@@ -91,21 +96,130 @@ pub struct Error {
 
 /// Add a Cache-Control header that marks the response as immutable and
 /// instructs clients to cache the response for 7 days.
-pub fn immutable_response(data: impl serde::Serialize) -> impl IntoResponse {
-    (
-        [(header::CACHE_CONTROL, "public, max-age=604800, immutable")],
-        Json(data),
-    )
+pub fn immutable_response(data: impl serde::Serialize) -> Response {
+    json_response(data, "public, max-age=604800, immutable".to_owned())
+}
+
+/// Add a Cache-Control header that instructs clients to revalidate the response before
+/// using it, with an ETag hashed from its body.
+pub fn revalidated_response(data: impl serde::Serialize) -> Response {
+    json_response(data, "no-cache".to_owned())
 }
 
 /// Add a Cache-Control header that marks the response as must-revalidate and
 /// instructs clients to cache the response for `max_age_seconds` .
-pub fn cached_response(data: impl serde::Serialize, max_age_in_seconds: u64) -> impl IntoResponse {
+pub fn cached_response(data: impl serde::Serialize, max_age_in_seconds: u64) -> Response {
+    json_response(
+        data,
+        format!("public, max-age={max_age_in_seconds}, must-revalidate"),
+    )
+}
+
+/// Add an ETag header derived from the state the response depends on, and instruct clients
+/// to revalidate the response before using it. See [`conditional_middleware`].
+pub fn etag_response(etag: HeaderValue, data: impl serde::Serialize) -> impl IntoResponse {
     (
-        [(
-            header::CACHE_CONTROL,
-            format!("public, max-age={max_age_in_seconds}, must-revalidate"),
-        )],
+        [
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, HeaderValue::from_static("no-cache")),
+        ],
         Json(data),
     )
 }
+
+/// A JSON response with the given Cache-Control header, and an ETag hashed from its body.
+fn json_response(data: impl serde::Serialize, cache_control: String) -> Response {
+    let body = match serde_json::to_vec(&data) {
+        Ok(body) => body,
+        Err(err) => return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    };
+    let etag = format!(
+        "\"{}\"",
+        BASE64_URL_SAFE_NO_PAD.encode(Sha256::digest(&body))
+    );
+
+    (
+        [
+            (header::CACHE_CONTROL, cache_control),
+            (header::CONTENT_TYPE, "application/json".to_owned()),
+            (header::ETAG, etag),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// Headers sent along with a `304 Not Modified` response, see RFC 9110, section 15.4.5.
+const NOT_MODIFIED_HEADERS: [header::HeaderName; 6] = [
+    header::CACHE_CONTROL,
+    header::CONTENT_LOCATION,
+    header::DATE,
+    header::ETAG,
+    header::EXPIRES,
+    header::VARY,
+];
+
+/// Answer conditional `GET` requests with `304 Not Modified` if the `If-None-Match` header
+/// matches the ETag of the response.
+///
+/// Handlers opt into this by setting an ETag on their response, eg. with [`etag_response`].
+pub async fn conditional_middleware(request: Request<Body>, next: Next) -> Response {
+    let if_none_match = request.headers().get(header::IF_NONE_MATCH).cloned();
+    let safe = request.method() == Method::GET || request.method() == Method::HEAD;
+    let response = next.run(request).await;
+
+    let (Some(if_none_match), true) = (if_none_match, safe) else {
+        return response;
+    };
+    if !response.status().is_success() {
+        return response;
+    }
+    let Some(etag) = response.headers().get(header::ETAG) else {
+        return response;
+    };
+    if !etag_matches(&if_none_match, etag) {
+        return response;
+    }
+
+    let mut not_modified = StatusCode::NOT_MODIFIED.into_response();
+    for name in NOT_MODIFIED_HEADERS {
+        for value in response.headers().get_all(&name) {
+            not_modified.headers_mut().append(&name, value.clone());
+        }
+    }
+    not_modified
+}
+
+/// Whether an `If-None-Match` header matches an ETag, using weak comparison.
+fn etag_matches(if_none_match: &HeaderValue, etag: &HeaderValue) -> bool {
+    let (Ok(if_none_match), Ok(etag)) = (if_none_match.to_str(), etag.to_str()) else {
+        return false;
+    };
+    let etag = etag.trim_start_matches("W/");
+
+    if_none_match.trim() == "*"
+        || if_none_match
+            .split(',')
+            .any(|tag| tag.trim().trim_start_matches("W/") == etag)
+}
+
+/// Prevent shared caches from storing responses to authenticated requests, since these can
+/// contain data that is only visible to the session.
+pub async fn private_cache_middleware(request: Request<Body>, next: Next) -> Response {
+    let authenticated = request.headers().contains_key(header::AUTHORIZATION);
+    let mut response = next.run(request).await;
+
+    if authenticated {
+        let headers = response.headers_mut();
+        let value = headers
+            .get(header::CACHE_CONTROL)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.replace("public", "private"));
+
+        if let Some(Ok(value)) = value.map(|v| HeaderValue::from_str(&v)) {
+            headers.insert(header::CACHE_CONTROL, value);
+        }
+        headers.append(header::VARY, HeaderValue::from_static("Authorization"));
+    }
+    response
+}
@@ -1,22 +1,333 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
 use std::num::NonZeroUsize;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
 use lru::LruCache;
-use tokio::sync::Mutex;
+use sha2::{Digest, Sha256};
 
 use radicle::prelude::RepoId;
-use radicle_surf::Oid;
 
+/// Endpoints with immutable responses, that can be cached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    /// `GET /repos/:rid/commits/:sha`
+    Commit,
+    /// `GET /repos/:rid/diff/:base/:oid`
+    Diff,
+    /// `GET /repos/:rid/tree/:sha/*path`
+    Tree,
+    /// `GET /repos/:rid/stats/tree/:sha`
+    TreeStats,
+    /// `GET /repos/:rid/blob/:sha/*path`
+    Blob,
+    /// `GET /repos/:rid/readme/:sha`
+    Readme,
+    /// `GET /repos/:rid/blame/:sha/*path`
+    Blame,
+    /// `GET /repos/:rid/patches/:id/revisions/:rev/interdiff/:other`
+    Interdiff,
+}
+
+impl Endpoint {
+    pub const ALL: [Self; 8] = [
+        Self::Commit,
+        Self::Diff,
+        Self::Tree,
+        Self::TreeStats,
+        Self::Blob,
+        Self::Readme,
+        Self::Blame,
+        Self::Interdiff,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Commit => "commit",
+            Self::Diff => "diff",
+            Self::Tree => "tree",
+            Self::TreeStats => "tree-stats",
+            Self::Blob => "blob",
+            Self::Readme => "readme",
+            Self::Blame => "blame",
+            Self::Interdiff => "interdiff",
+        }
+    }
+}
+
+impl FromStr for Endpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|e| e.as_str() == s)
+            .ok_or_else(|| format!("unknown cache endpoint '{s}'"))
+    }
+}
+
+/// Identifies a cached response.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub endpoint: Endpoint,
+    pub rid: RepoId,
+    /// Identifies the response among the responses of the endpoint for the repository,
+    /// eg. a commit and path.
+    pub id: String,
+}
+
+/// Where cached responses are stored.
+pub trait Store: Send + Sync {
+    /// Get a cached response.
+    fn get(&self, key: &Key) -> Option<serde_json::Value>;
+    /// Cache a response, evicting other responses if the store is full.
+    fn put(&self, key: Key, value: &serde_json::Value);
+}
+
+/// Caches responses of immutable endpoints.
 #[derive(Clone)]
 pub struct Cache {
-    pub tree: Arc<Mutex<LruCache<(RepoId, Oid, String), serde_json::Value>>>,
+    store: Arc<dyn Store>,
+    endpoints: HashSet<Endpoint>,
 }
 
 impl Cache {
-    /// Creates a new cache of the given size.
+    /// Creates a cache using the given store, for the given endpoints.
+    pub fn with_store(
+        store: impl Store + 'static,
+        endpoints: impl IntoIterator<Item = Endpoint>,
+    ) -> Self {
+        Self {
+            store: Arc::new(store),
+            endpoints: endpoints.into_iter().collect(),
+        }
+    }
+
+    /// Get the cached response of an endpoint.
+    pub fn get(
+        &self,
+        endpoint: Endpoint,
+        rid: RepoId,
+        id: impl ToString,
+    ) -> Option<serde_json::Value> {
+        if !self.endpoints.contains(&endpoint) {
+            return None;
+        }
+        self.store.get(&Key {
+            endpoint,
+            rid,
+            id: id.to_string(),
+        })
+    }
+
+    /// Cache the response of an endpoint.
+    pub fn put(
+        &self,
+        endpoint: Endpoint,
+        rid: RepoId,
+        id: impl ToString,
+        value: &serde_json::Value,
+    ) {
+        if !self.endpoints.contains(&endpoint) {
+            return;
+        }
+        self.store.put(
+            Key {
+                endpoint,
+                rid,
+                id: id.to_string(),
+            },
+            value,
+        )
+    }
+
+    /// Get the cached response of an endpoint, or compute and cache it.
+    /// Errors are not cached.
+    pub fn get_or_insert<E>(
+        &self,
+        endpoint: Endpoint,
+        rid: RepoId,
+        id: impl ToString,
+        f: impl FnOnce() -> Result<serde_json::Value, E>,
+    ) -> Result<serde_json::Value, E> {
+        let id = id.to_string();
+        if let Some(value) = self.get(endpoint, rid, &id) {
+            return Ok(value);
+        }
+        let value = f()?;
+        self.put(endpoint, rid, id, &value);
+
+        Ok(value)
+    }
+}
+
+/// In-memory store, evicting the least recently used responses.
+pub struct Memory {
+    responses: Mutex<LruCache<Key, serde_json::Value>>,
+}
+
+impl Memory {
+    /// Creates a new store holding up to `size` responses.
     pub fn new(size: NonZeroUsize) -> Self {
-        Cache {
-            tree: Arc::new(Mutex::new(LruCache::new(size))),
+        Self {
+            responses: Mutex::new(LruCache::new(size)),
+        }
+    }
+}
+
+impl Store for Memory {
+    fn get(&self, key: &Key) -> Option<serde_json::Value> {
+        let mut responses = self.responses.lock().ok()?;
+        responses.get(key).cloned()
+    }
+
+    fn put(&self, key: Key, value: &serde_json::Value) {
+        if let Ok(mut responses) = self.responses.lock() {
+            responses.put(key, value.clone());
+        }
+    }
+}
+
+/// On-disk store, persisted across restarts. Once the responses stored take up more than
+/// the maximum size, the least recently used ones are evicted.
+///
+/// The index is only locked while it is read or updated, not during file I/O, so that
+/// concurrent requests don't wait on each other's reads and writes.
+pub struct Disk {
+    dir: PathBuf,
+    max_size: u64,
+    index: Mutex<DiskIndex>,
+    /// Used to name temporary files, so that concurrent writes don't share one.
+    writes: AtomicU64,
+}
+
+/// Sizes of the files in an on-disk store, by least recent use.
+struct DiskIndex {
+    files: LruCache<String, u64>,
+    size: u64,
+}
+
+impl Disk {
+    /// File extension of cached responses.
+    const EXTENSION: &'static str = "json";
+
+    /// Open an on-disk store in the given directory, creating it if needed.
+    pub fn open(dir: impl AsRef<Path>, max_size: u64) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        // Files written most recently are considered most recently used.
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(Self::EXTENSION) {
+                continue;
+            }
+            let meta = entry.metadata()?;
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                files.push((meta.modified()?, name.to_owned(), meta.len()));
+            }
+        }
+        files.sort();
+
+        let mut index = DiskIndex {
+            files: LruCache::unbounded(),
+            size: 0,
+        };
+        for (_, name, size) in files {
+            index.files.put(name, size);
+            index.size += size;
+        }
+        let evicted = disk_evict(&mut index, max_size);
+        let disk = Self {
+            dir,
+            max_size,
+            index: Mutex::new(index),
+            writes: AtomicU64::new(0),
+        };
+        disk.remove(evicted);
+
+        Ok(disk)
+    }
+
+    /// Name of the file a response is stored in.
+    fn file_name(key: &Key) -> String {
+        let hash = Sha256::digest(format!("{}/{}", key.rid, key.id));
+        format!("{}-{:x}.{}", key.endpoint.as_str(), hash, Self::EXTENSION)
+    }
+
+    /// Remove the files of evicted responses.
+    fn remove(&self, evicted: Vec<String>) {
+        for name in evicted {
+            if let Err(err) = fs::remove_file(self.dir.join(&name)) {
+                tracing::warn!("cache: error evicting {name}: {err}");
+            }
+        }
+    }
+}
+
+/// Evict the least recently used responses from the index until the store fits its maximum
+/// size, returning the names of the files to remove.
+fn disk_evict(index: &mut DiskIndex, max_size: u64) -> Vec<String> {
+    let mut evicted = Vec::new();
+    while index.size > max_size {
+        let Some((name, size)) = index.files.pop_lru() else {
+            break;
+        };
+        index.size -= size;
+        evicted.push(name);
+    }
+    evicted
+}
+
+impl Store for Disk {
+    fn get(&self, key: &Key) -> Option<serde_json::Value> {
+        let name = Self::file_name(key);
+        self.index.lock().ok()?.files.get(&name)?;
+
+        match fs::read(self.dir.join(&name)) {
+            Ok(bytes) => serde_json::from_slice(&bytes).ok(),
+            Err(err) => {
+                tracing::warn!("cache: error reading {name}: {err}");
+                let mut index = self.index.lock().ok()?;
+                if let Some(size) = index.files.pop(&name) {
+                    index.size -= size;
+                }
+                None
+            }
+        }
+    }
+
+    fn put(&self, key: Key, value: &serde_json::Value) {
+        let name = Self::file_name(&key);
+        let Ok(bytes) = serde_json::to_vec(value) else {
+            return;
+        };
+        // Write to a temporary file first, so that a crash doesn't leave a truncated response.
+        let write = self.writes.fetch_add(1, Ordering::Relaxed);
+        let tmp = self.dir.join(format!("{name}.{write}.tmp"));
+        if let Err(err) =
+            fs::write(&tmp, &bytes).and_then(|()| fs::rename(&tmp, self.dir.join(&name)))
+        {
+            tracing::warn!("cache: error writing {name}: {err}");
+            fs::remove_file(&tmp).ok();
+            return;
         }
+        let evicted = {
+            let Ok(mut index) = self.index.lock() else {
+                return;
+            };
+            if let Some(size) = index.files.put(name, bytes.len() as u64) {
+                index.size -= size;
+            }
+            index.size += bytes.len() as u64;
+            disk_evict(&mut index, self.max_size)
+        };
+        self.remove(evicted);
     }
 }
@@ -14,9 +14,33 @@ pub enum GitError {
     #[error("i/o error: {0}")]
     Io(#[from] std::io::Error),
 
-    /// The service is not available.
-    #[error("service '{0}' not available")]
-    ServiceUnavailable(&'static str),
+    /// The request is not authenticated.
+    #[error("unauthorized")]
+    Unauthorized,
+
+    /// The authenticated session is not allowed to perform this request.
+    #[error("forbidden")]
+    Forbidden,
+
+    /// The request has an invalid HTTP signature.
+    #[error("http signature: {0}")]
+    Signature(#[from] crate::api::auth::SignatureError),
+
+    /// A push tried to update a reference that can't be pushed to.
+    #[error("pushing to '{0}' is not allowed")]
+    RefRejected(String),
+
+    /// Invalid `git-receive-pack` request.
+    #[error("invalid pkt-line in request")]
+    InvalidPktLine,
+
+    /// Git error.
+    #[error(transparent)]
+    Git2(#[from] radicle::git::raw::Error),
+
+    /// Profile error.
+    #[error("profile: {0}")]
+    Profile(#[from] radicle::profile::Error),
 
     /// Invalid identifier.
     #[error("invalid radicle identifier: {0}")]
@@ -30,6 +54,10 @@ pub enum GitError {
     #[error("repository: {0}")]
     Repository(#[from] radicle::storage::RepositoryError),
 
+    /// Sessions database error.
+    #[error("sessions: {0}")]
+    Sessions(#[from] sqlite::Error),
+
     /// Git backend error.
     #[error("git-http-backend: exited with code {0}")]
     BackendExited(ExitStatus),
@@ -50,7 +78,9 @@ pub enum GitError {
 impl GitError {
     pub fn status(&self) -> http::StatusCode {
         match self {
-            GitError::ServiceUnavailable(_) => http::StatusCode::SERVICE_UNAVAILABLE,
+            GitError::Unauthorized | GitError::Signature(_) => http::StatusCode::UNAUTHORIZED,
+            GitError::Forbidden | GitError::RefRejected(_) => http::StatusCode::FORBIDDEN,
+            GitError::InvalidPktLine => http::StatusCode::BAD_REQUEST,
             GitError::Id(_) => http::StatusCode::NOT_FOUND,
             GitError::NotFound => http::StatusCode::NOT_FOUND,
             _ => http::StatusCode::INTERNAL_SERVER_ERROR,
@@ -62,7 +92,18 @@ impl IntoResponse for GitError {
     fn into_response(self) -> Response {
         tracing::error!("{}", self);
 
-        self.status().into_response()
+        match self {
+            // Prompts git clients for credentials.
+            GitError::Unauthorized => (
+                self.status(),
+                [(http::header::WWW_AUTHENTICATE, "Basic realm=\"radicle\"")],
+            )
+                .into_response(),
+            GitError::RefRejected(_) | GitError::Signature(_) => {
+                (self.status(), self.to_string()).into_response()
+            }
+            _ => self.status().into_response(),
+        }
     }
 }
 
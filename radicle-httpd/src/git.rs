@@ -1,41 +1,60 @@
 use std::collections::HashMap;
-use std::io::prelude::*;
+use std::io::Write as _;
 use std::net::SocketAddr;
 use std::path::Path;
-use std::process::{Command, Stdio};
-use std::sync::Arc;
-use std::{io, net, str};
+use std::process::Stdio;
+use std::{io, mem, net, str};
 
-use axum::body::Bytes;
-use axum::extract::{ConnectInfo, Path as AxumPath, RawQuery, State};
+use axum::body::{Body, Bytes};
+use axum::extract::{ConnectInfo, OriginalUri, Path as AxumPath, RawQuery, State};
 use axum::http::header::HeaderName;
-use axum::http::{HeaderMap, Method, StatusCode};
+use axum::http::{HeaderMap, HeaderValue, Method, StatusCode};
 use axum::response::IntoResponse;
 use axum::routing::any;
 use axum::Router;
 use flate2::write::GzDecoder;
-use hyper::body::Buf as _;
+use futures_util::stream::{self, BoxStream, StreamExt as _};
+use tokio::io::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _, BufReader};
+use tokio::process::{Child, Command};
+use tokio::task::JoinHandle;
+use tokio_util::io::ReaderStream;
+
+use radicle::identity::{DocAt, RepoId};
+use radicle::storage::{
+    ReadRepository, ReadStorage, SignRepository, WriteRepository, WriteStorage,
+};
+
+use crate::api::auth::{self, Session};
+use crate::api::Context;
+use crate::error::GitError as Error;
+use crate::GitBackend;
 
-use radicle::identity::RepoId;
-use radicle::profile::Profile;
-use radicle::storage::{ReadRepository, ReadStorage};
+mod native;
 
-use crate::error::GitError as Error;
+/// Reference prefixes delegates are allowed to push to.
+const PUSHABLE_REFS: [&str; 2] = ["refs/heads/", "refs/tags/"];
+
+/// Max size of the reference updates at the start of a push request.
+const MAX_PUSH_COMMANDS_SIZE: usize = 1_048_576;
 
-pub fn router(profile: Arc<Profile>, aliases: HashMap<String, RepoId>) -> Router {
+/// Request headers carrying credentials, which are kept out of the logs.
+const CREDENTIAL_HEADERS: [&str; 3] = ["authorization", "cookie", "signature"];
+
+pub fn router(ctx: Context, aliases: HashMap<String, RepoId>, backend: GitBackend) -> Router {
     Router::new()
         .route("/:rid/*request", any(git_handler))
-        .with_state((profile, aliases))
+        .with_state((ctx, aliases, backend))
 }
 
 async fn git_handler(
-    State((profile, aliases)): State<(Arc<Profile>, HashMap<String, RepoId>)>,
+    State((ctx, aliases, backend)): State<(Context, HashMap<String, RepoId>, GitBackend)>,
     AxumPath((repository, request)): AxumPath<(String, String)>,
+    OriginalUri(uri): OriginalUri,
     method: Method,
     headers: HeaderMap,
     ConnectInfo(remote): ConnectInfo<SocketAddr>,
     query: RawQuery,
-    body: Bytes,
+    body: Body,
 ) -> impl IntoResponse {
     let query = query.0.unwrap_or_default();
     let name = repository.strip_suffix(".git").unwrap_or(&repository);
@@ -49,8 +68,41 @@ async fn git_handler(
         }
     };
 
+    let session = match ctx.sessions().authenticate(&headers)? {
+        Some(session) => Some(session),
+        None => auth::signed_session(&method, &uri, &headers, chrono::Utc::now().timestamp())?,
+    };
+    let body = match backend {
+        GitBackend::Native => {
+            match native::serve(
+                &ctx,
+                rid,
+                session.as_ref(),
+                &method,
+                &request,
+                &query,
+                &headers,
+                body,
+            )
+            .await?
+            {
+                native::Served::Response(response) => return Ok(response),
+                native::Served::Fallback(body) => body,
+            }
+        }
+        GitBackend::HttpBackend => body,
+    };
     let (status, headers, body) = git_http_backend(
-        &profile, method, headers, body, remote, rid, &request, query,
+        &ctx,
+        method,
+        headers,
+        body,
+        remote,
+        rid,
+        &request,
+        query,
+        session.as_ref(),
+        backend,
     )
     .await?;
 
@@ -62,19 +114,37 @@ async fn git_handler(
         }
     }
 
-    Ok::<_, Error>((status, response_headers, body))
+    Ok::<_, Error>((status, response_headers, body).into_response())
+}
+
+/// Get the identity document of a repository, if it can be viewed with the given session.
+fn identity(ctx: &Context, id: RepoId, session: Option<&Session>) -> Result<DocAt, Error> {
+    let doc = ctx.profile().storage.repository(id)?.identity_doc()?;
+
+    // Only allow cloning private repositories with a session they are visible to.
+    if !auth::can_view(&doc, session) {
+        return Err(Error::NotFound);
+    }
+    Ok(doc)
 }
 
+/// Proxy a request to `git http-backend`.
+///
+/// The request body is streamed to the backend while its output is read, and the output is
+/// streamed back as the response body, so that neither is held in memory.
 async fn git_http_backend(
-    profile: &Profile,
+    ctx: &Context,
     method: Method,
     headers: HeaderMap,
-    mut body: Bytes,
+    body: Body,
     remote: net::SocketAddr,
     id: RepoId,
     path: &str,
     query: String,
-) -> Result<(StatusCode, HashMap<String, Vec<String>>, Vec<u8>), Error> {
+    session: Option<&Session>,
+    backend: GitBackend,
+) -> Result<(StatusCode, HashMap<String, Vec<String>>, Body), Error> {
+    let profile = ctx.profile();
     let git_dir = radicle::storage::git::paths::repository(&profile.storage, &id);
     let content_type =
         if let Some(Ok(content_type)) = headers.get("Content-Type").map(|h| h.to_str()) {
@@ -83,27 +153,78 @@ async fn git_http_backend(
             ""
         };
 
-    // Don't allow cloning of private repositories.
-    let doc = profile.storage.repository(id)?.identity_doc()?;
-    if doc.visibility().is_private() {
-        return Err(Error::NotFound);
-    }
+    let doc = identity(ctx, id, session)?;
 
-    // Reject push requests.
-    match (path, query.as_str()) {
+    // Only allow pushes from delegates, into their own namespace. Since `rad/sigrefs` has to be
+    // signed after a push, only the node's own key can push.
+    let push = match (path, query.as_str()) {
         ("git-receive-pack", _) | (_, "service=git-receive-pack") => {
-            return Err(Error::ServiceUnavailable("git-receive-pack"));
+            let session = session.ok_or(Error::Unauthorized)?;
+            if session.public_key != profile.public_key
+                || !doc.is_delegate(&session.public_key.into())
+            {
+                return Err(Error::Forbidden);
+            }
+            Some(session.public_key)
         }
-        _ => {}
+        _ => None,
     };
+    let receive = push.is_some() && path == "git-receive-pack";
+
+    // Whether the request body is compressed.
+    let gzip = matches!(
+        headers.get("Content-Encoding").map(|h| h.to_str()),
+        Some(Ok("gzip"))
+    );
+    let mut body = request_body(body, gzip);
+
+    // Check the reference updates of a push before anything is written to storage.
+    let mut commands = Vec::new();
+    if receive {
+        let refs = loop {
+            if let Some(refs) = pushed_refs(&commands)? {
+                break refs;
+            }
+            if commands.len() > MAX_PUSH_COMMANDS_SIZE {
+                return Err(Error::InvalidPktLine);
+            }
+            match body.next().await {
+                Some(chunk) => commands.extend_from_slice(&chunk?),
+                None => return Err(Error::InvalidPktLine),
+            }
+        };
+        for refname in refs {
+            if !PUSHABLE_REFS.iter().any(|p| refname.starts_with(p)) {
+                return Err(Error::RefRejected(refname));
+            }
+        }
+    }
 
     tracing::debug!("id: {:?}", id);
-    tracing::debug!("headers: {:?}", headers);
+    tracing::debug!("headers: {:?}", redacted(&headers));
     tracing::debug!("path: {:?}", path);
     tracing::debug!("method: {:?}", method.as_str());
     tracing::debug!("remote: {:?}", remote.to_string());
 
     let mut cmd = Command::new("git");
+    // The native backend advertises protocol v2, so the requests it falls back on, such as
+    // shallow fetches, have to be served with v2 too. It also falls back on requests that are
+    // larger than git's default request buffer. `git http-backend` otherwise keeps serving
+    // the versions and request sizes it did before.
+    if backend == GitBackend::Native {
+        if let Some(protocol) = headers.get("Git-Protocol").and_then(|h| h.to_str().ok()) {
+            cmd.env("GIT_PROTOCOL", protocol);
+        }
+        cmd.env(
+            "GIT_HTTP_MAX_REQUEST_BUFFER",
+            native::MAX_FALLBACK_REQUEST_SIZE.to_string(),
+        );
+    }
+    if let Some(nid) = push {
+        cmd.args(["-c", "http.receivepack=true"])
+            .env("GIT_NAMESPACE", nid.to_string())
+            .env("REMOTE_USER", nid.to_human());
+    }
     let mut child = cmd
         // This is a workaround to allow fetching particular commits by their OID.
         // Otherwise, the client errors with "Server does not allow request for unadvertised object"
@@ -124,93 +245,219 @@ async fn git_http_backend(
         .stdin(Stdio::piped())
         .spawn()?;
 
-    // Whether the request body is compressed.
-    let gzip = matches!(
-        headers.get("Content-Encoding").map(|h| h.to_str()),
-        Some(Ok("gzip"))
-    );
+    // These are safe because we captured the child's stdio.
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stderr = child.stderr.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+    // Copy the request body to git-http-backend's stdin, while its output is being read.
+    tokio::spawn(async move {
+        let result: io::Result<()> = async {
+            stdin.write_all(&commands).await?;
+            while let Some(chunk) = body.next().await {
+                stdin.write_all(&chunk?).await?;
+            }
+            stdin.shutdown().await
+        }
+        .await;
 
-    {
-        // This is safe because we captured the child's stdin.
-        let mut stdin = child.stdin.take().unwrap();
+        if let Err(err) = result {
+            tracing::debug!("git-http-backend: error writing request body: {err}");
+        }
+    });
+    let stderr = tokio::spawn(async move {
+        let mut output = Vec::new();
+        stderr.read_to_end(&mut output).await.ok();
+        output
+    });
+
+    // Parse headers returned by git so that we can use them in the client response.
+    let mut headers = HashMap::new();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if stdout.read_line(&mut line).await? == 0 {
+            wait(child, stderr, id).await?;
+            return Ok((StatusCode::OK, headers, Body::empty()));
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            break;
+        }
 
-        // Copy the request body to git-http-backend's stdin.
-        if gzip {
-            let mut decoder = GzDecoder::new(&mut stdin);
-            let mut reader = body.reader();
+        let mut parts = line.splitn(2, ':');
+        let key = parts.next();
+        let value = parts.next();
 
-            io::copy(&mut reader, &mut decoder)?;
-            decoder.finish()?;
+        if let (Some(key), Some(value)) = (key, value) {
+            let value = &value[1..];
+
+            headers
+                .entry(key.to_string())
+                .or_insert_with(Vec::new)
+                .push(value.to_string());
         } else {
-            while body.has_remaining() {
-                let mut chunk = body.chunk();
-                let count = chunk.len();
+            return Err(Error::BackendHeader(line.to_owned()));
+        }
+    }
 
-                io::copy(&mut chunk, &mut stdin)?;
-                body.advance(count);
-            }
+    let status = {
+        tracing::debug!("git-http-backend: {:?}", &headers);
+
+        let line = headers.remove("Status").unwrap_or_default();
+        let line = line.into_iter().next().unwrap_or_default();
+        let mut parts = line.split(' ');
+
+        parts
+            .next()
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(StatusCode::OK)
+    };
+
+    // The response to a push is only sent once the pushed refs are signed, so that clients
+    // don't see a successful push before it is visible to the network. It only consists of
+    // the status of each reference update, so it is small enough to buffer.
+    if receive {
+        let mut body = Vec::new();
+        stdout.read_to_end(&mut body).await?;
+        wait(child, stderr, id).await?;
+
+        if status.is_success() {
+            let ctx = ctx.clone();
+            tokio::task::spawn_blocking(move || post_receive(&ctx, id))
+                .await
+                .map_err(io::Error::other)??;
         }
+        return Ok((status, headers, Body::from(body)));
     }
 
-    match child.wait_with_output() {
-        Ok(output) if output.status.success() => {
-            tracing::info!("git-http-backend: exited successfully for {}", id);
+    tokio::spawn(async move {
+        // Errors are logged by `wait`, there is no one left to report them to.
+        wait(child, stderr, id).await.ok();
+    });
 
-            let mut reader = std::io::Cursor::new(output.stdout);
-            let mut headers = HashMap::new();
+    Ok((
+        status,
+        headers,
+        Body::from_stream(ReaderStream::new(stdout)),
+    ))
+}
 
-            // Parse headers returned by git so that we can use them in the client response.
-            for line in io::Read::by_ref(&mut reader).lines() {
-                let line = line?;
+/// Wait for `git http-backend` to exit, logging its error output if it failed.
+/// Request headers with their credentials redacted, for logging.
+fn redacted(headers: &HeaderMap) -> HeaderMap {
+    let mut headers = headers.clone();
+    for name in CREDENTIAL_HEADERS {
+        if headers.contains_key(name) {
+            headers.insert(name, HeaderValue::from_static("<redacted>"));
+        }
+    }
+    headers
+}
 
-                if line.is_empty() || line == "\r" {
-                    break;
-                }
+async fn wait(mut child: Child, stderr: JoinHandle<Vec<u8>>, id: RepoId) -> Result<(), Error> {
+    let status = child.wait().await?;
+    let stderr = stderr.await.unwrap_or_default();
 
-                let mut parts = line.splitn(2, ':');
-                let key = parts.next();
-                let value = parts.next();
+    if status.success() {
+        tracing::info!("git-http-backend: exited successfully for {}", id);
+        return Ok(());
+    }
+    if let Ok(output) = str::from_utf8(&stderr) {
+        tracing::error!("git-http-backend: stderr: {}", output.trim_end());
+    }
+    Err(Error::BackendExited(status))
+}
 
-                if let (Some(key), Some(value)) = (key, value) {
-                    let value = &value[1..];
+/// Stream a request body, decompressing it if it is gzip encoded.
+fn request_body(body: Body, gzip: bool) -> BoxStream<'static, io::Result<Bytes>> {
+    let body = body.into_data_stream().map(|c| c.map_err(io::Error::other));
+    if !gzip {
+        return body.boxed();
+    }
+    let decoder = GzDecoder::new(Vec::new());
 
-                    headers
-                        .entry(key.to_string())
-                        .or_insert_with(Vec::new)
-                        .push(value.to_string());
-                } else {
-                    return Err(Error::BackendHeader(line));
-                }
-            }
+    stream::unfold((body, Some(decoder)), |(mut body, decoder)| async move {
+        let mut decoder = decoder?;
 
-            let status = {
-                tracing::debug!("git-http-backend: {:?}", &headers);
+        match body.next().await {
+            Some(Ok(chunk)) => {
+                let result = decoder
+                    .write_all(&chunk)
+                    .map(|()| Bytes::from(mem::take(decoder.get_mut())));
+                let decoder = result.is_ok().then_some(decoder);
 
-                let line = headers.remove("Status").unwrap_or_default();
-                let line = line.into_iter().next().unwrap_or_default();
-                let mut parts = line.split(' ');
+                Some((result, (body, decoder)))
+            }
+            Some(Err(err)) => Some((Err(err), (body, None))),
+            None => Some((decoder.finish().map(Bytes::from), (body, None))),
+        }
+    })
+    .boxed()
+}
 
-                parts
-                    .next()
-                    .and_then(|p| p.parse().ok())
-                    .unwrap_or(StatusCode::OK)
-            };
+/// Update the repository after a successful push: sign the pushed refs, update the canonical
+/// head, and announce the new refs to the network.
+fn post_receive(ctx: &Context, rid: RepoId) -> Result<(), Error> {
+    let profile = ctx.profile();
+    let signer = profile.signer()?;
+    let repo = profile.storage.repository_mut(rid)?;
+
+    repo.sign_refs(&signer)?;
+    // The pushed refs are already in storage at this point, so the push succeeded even if
+    // the canonical head can't be updated, eg. because the delegates don't agree on it.
+    if let Err(err) = repo.set_head() {
+        tracing::warn!("git-http-backend: failed to set head of {rid}: {err}");
+    }
+    ctx.announce_refs(rid);
 
-            let position = reader.position() as usize;
-            let body = reader.into_inner().split_off(position);
+    Ok(())
+}
 
-            Ok((status, headers, body))
+/// Parse the names of the references updated by a `git-receive-pack` request.
+///
+/// The request starts with one `<old-oid> <new-oid> <refname>` pkt-line per reference, the
+/// first of which also lists capabilities after a NUL byte, followed by a flush-pkt.
+/// Returns `None` if the given data doesn't contain all reference updates yet.
+fn pushed_refs(body: &[u8]) -> Result<Option<Vec<String>>, Error> {
+    let mut refs = Vec::new();
+    let mut rest = body;
+
+    loop {
+        let Some(len) = rest.get(..4) else {
+            return Ok(None);
+        };
+        let len = str::from_utf8(len)
+            .ok()
+            .and_then(|l| usize::from_str_radix(l, 16).ok())
+            .ok_or(Error::InvalidPktLine)?;
+        // Flush-pkt, marking the end of the reference updates.
+        if len == 0 {
+            break;
         }
-        Ok(output) => {
-            if let Ok(output) = std::str::from_utf8(&output.stderr) {
-                tracing::error!("git-http-backend: stderr: {}", output.trim_end());
-            }
-            Err(Error::BackendExited(output.status))
+        if len < 4 {
+            return Err(Error::InvalidPktLine);
         }
-        Err(err) => {
-            panic!("failed to wait for git-http-backend: {err}");
+        let Some(line) = rest.get(4..len) else {
+            return Ok(None);
+        };
+        rest = &rest[len..];
+
+        let line = line.split(|b| *b == 0).next().unwrap_or_default();
+        let line = str::from_utf8(line).map_err(|_| Error::InvalidPktLine)?;
+        // Shallow clients list their shallow commits before the reference updates.
+        if line.starts_with("shallow ") {
+            continue;
         }
+        let refname = line
+            .trim_end()
+            .splitn(3, ' ')
+            .nth(2)
+            .ok_or(Error::InvalidPktLine)?;
+
+        refs.push(refname.to_owned());
     }
+    Ok(Some(refs))
 }
 
 #[cfg(test)]
@@ -219,17 +466,64 @@ mod routes {
     use std::net::SocketAddr;
     use std::str::FromStr;
 
+    use axum::body::Body;
     use axum::extract::connect_info::MockConnectInfo;
-    use axum::http::StatusCode;
+    use axum::http::{Request, StatusCode};
+    use base64::prelude::{Engine as _, BASE64_STANDARD};
     use radicle::identity::RepoId;
+    use tower::ServiceExt as _;
+
+    use radicle::storage::{ReadRepository, ReadStorage, RemoteRepository};
+
+    use super::*;
+    use crate::test::{self, get, get_auth, HEAD, RID, RID_PRIVATE};
 
-    use crate::test::{self, get, RID};
+    /// Serve the git routes over TCP, returning the base URL.
+    async fn serve(ctx: &Context, backend: GitBackend) -> String {
+        let app = super::router(ctx.clone(), HashMap::new(), backend)
+            .into_make_service_with_connect_info::<SocketAddr>();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        url
+    }
+
+    /// Run `git` in the given directory, ignoring user and system configuration.
+    async fn git(dir: &std::path::Path, args: &[&str]) -> std::process::Output {
+        let args = args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        let dir = dir.to_owned();
+
+        tokio::task::spawn_blocking(move || {
+            std::fs::create_dir_all(&dir).unwrap();
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(dir)
+                .env("GIT_CONFIG_NOSYSTEM", "1")
+                .env("GIT_CONFIG_GLOBAL", "/dev/null")
+                .env("GIT_TERMINAL_PROMPT", "0")
+                .output()
+                .unwrap()
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn response_body(response: axum::response::Response) -> Vec<u8> {
+        let mut body = Vec::new();
+        let mut stream = response.into_body().into_data_stream();
+        while let Some(chunk) = stream.next().await {
+            body.extend_from_slice(&chunk.unwrap());
+        }
+        body
+    }
 
     #[tokio::test]
     async fn test_info_request() {
         let tmp = tempfile::tempdir().unwrap();
         let ctx = test::seed(tmp.path());
-        let app = super::router(ctx.profile().to_owned(), HashMap::new())
+        let app = super::router(ctx.clone(), HashMap::new(), GitBackend::default())
             .layer(MockConnectInfo(SocketAddr::from(([0, 0, 0, 0], 8080))));
 
         let response = get(&app, format!("/{RID}.git/info/refs")).await;
@@ -237,13 +531,77 @@ mod routes {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_info_request_protocol() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let request = || {
+            Request::builder()
+                .uri(format!("/{RID}.git/info/refs?service=git-upload-pack"))
+                .header("Git-Protocol", "version=2")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        for (backend, v2) in [(GitBackend::HttpBackend, false), (GitBackend::Native, true)] {
+            let app = super::router(ctx.clone(), HashMap::new(), backend)
+                .layer(MockConnectInfo(SocketAddr::from(([0, 0, 0, 0], 8080))));
+            let response = app.oneshot(request()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let body = response_body(response).await;
+            assert_eq!(
+                body.windows(9).any(|w| w == b"version 2"),
+                v2,
+                "{backend:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_native_large_request() {
+        use flate2::write::GzEncoder;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let app = super::router(ctx.clone(), HashMap::new(), GitBackend::Native)
+            .layer(MockConnectInfo(SocketAddr::from(([0, 0, 0, 0], 8080))));
+
+        // An `ls-refs` command that is too large to be served natively once decoded.
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"0014command=ls-refs\n0001").unwrap();
+        for _ in 0..(1 << 20) {
+            encoder.write_all(b"0015ref-prefix refs/\n").unwrap();
+        }
+        encoder.write_all(b"0000").unwrap();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/{RID}.git/git-upload-pack"))
+            .header("Content-Type", "application/x-git-upload-pack-request")
+            .header("Content-Encoding", "gzip")
+            .header("Git-Protocol", "version=2")
+            .body(Body::from(encoder.finish().unwrap()))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response_body(response).await;
+        assert!(
+            body.windows(HEAD.len()).any(|w| w == HEAD.as_bytes()),
+            "{}",
+            String::from_utf8_lossy(&body)
+        );
+    }
+
     #[tokio::test]
     async fn test_aliases() {
         let tmp = tempfile::tempdir().unwrap();
         let ctx = test::seed(tmp.path());
         let app = super::router(
-            ctx.profile().to_owned(),
+            ctx.clone(),
             HashMap::from_iter([(String::from("heartwood"), RepoId::from_str(RID).unwrap())]),
+            GitBackend::default(),
         )
         .layer(MockConnectInfo(SocketAddr::from(([0, 0, 0, 0], 8080))));
 
@@ -253,4 +611,338 @@ mod routes {
         let response = get(&app, "/heartwood.git/info/refs").await;
         assert_eq!(response.status(), StatusCode::OK);
     }
+
+    #[tokio::test]
+    async fn test_info_request_private() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let app = super::router(ctx.clone(), HashMap::new(), GitBackend::default())
+            .layer(MockConnectInfo(SocketAddr::from(([0, 0, 0, 0], 8080))));
+        let token = test::create_session(&ctx);
+        let path = format!("/{RID_PRIVATE}.git/info/refs");
+
+        let response = get(&app, &path).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = get_auth(&app, &path, token.clone()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Git clients pass the token as a basic authentication password.
+        let credentials = BASE64_STANDARD.encode(format!("git:{token}"));
+        let request = Request::builder()
+            .uri(&path)
+            .header("Authorization", format!("Basic {credentials}"))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_upload_pack_streamed() {
+        use flate2::write::GzEncoder;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let app = super::router(ctx.clone(), HashMap::new(), GitBackend::default())
+            .layer(MockConnectInfo(SocketAddr::from(([0, 0, 0, 0], 8080))));
+
+        // A protocol v0 fetch of the head commit, gzip encoded and sent in small chunks.
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder
+            .write_all(format!("0032want {HEAD}\n00000009done\n").as_bytes())
+            .unwrap();
+        let body = encoder.finish().unwrap();
+        let chunks = body
+            .chunks(8)
+            .map(|c| Ok::<_, io::Error>(Bytes::copy_from_slice(c)))
+            .collect::<Vec<_>>();
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/{RID}.git/git-upload-pack"))
+            .header("Content-Type", "application/x-git-upload-pack-request")
+            .header("Content-Encoding", "gzip")
+            .body(Body::from_stream(stream::iter(chunks)))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("Content-Type").unwrap(),
+            "application/x-git-upload-pack-result"
+        );
+
+        let body = response_body(response).await;
+        assert!(body.starts_with(b"0008NAK\n"));
+        assert_eq!(&body[8..12], b"PACK");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_push() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let token = test::create_session(&ctx);
+        let nid = ctx.profile().public_key;
+        let url = format!("{}/{RID}.git", serve(&ctx, GitBackend::default()).await);
+        let work = tmp.path().join("work");
+        let auth = format!("http.extraHeader=Authorization: Bearer {token}");
+
+        assert!(git(&work, &["clone", &url, "."]).await.status.success());
+        assert!(git(
+            &work,
+            &[
+                "-c",
+                "user.name=Alice",
+                "-c",
+                "user.email=alice@radicle.xyz",
+                "commit",
+                "--allow-empty",
+                "-m",
+                "Pushed over HTTP"
+            ]
+        )
+        .await
+        .status
+        .success());
+
+        let head = git(&work, &["rev-parse", "HEAD"]).await.stdout;
+        let head = radicle::git::Oid::from_str(str::from_utf8(&head).unwrap().trim()).unwrap();
+
+        // Pushing requires a session.
+        let output = git(&work, &["push", "origin", "HEAD:refs/heads/feature"]).await;
+        assert!(!output.status.success());
+
+        // Only branches and tags can be pushed to.
+        let output = git(
+            &work,
+            &["-c", &auth, "push", "origin", "HEAD:refs/rad/other"],
+        )
+        .await;
+        assert!(!output.status.success());
+
+        let output = git(
+            &work,
+            &["-c", &auth, "push", "origin", "HEAD:refs/heads/feature"],
+        )
+        .await;
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let repo = ctx
+            .profile()
+            .storage
+            .repository(RepoId::from_str(RID).unwrap())
+            .unwrap();
+        let refname = radicle::git::RefString::try_from("refs/heads/feature").unwrap();
+        let refname = radicle::git::Qualified::from_refstr(&refname).unwrap();
+
+        assert_eq!(repo.reference_oid(&nid, &refname).unwrap(), head);
+        assert_eq!(
+            repo.remote(&nid).unwrap().refs.get(&refname),
+            Some(head),
+            "pushed refs are signed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_push_signature() {
+        use radicle::crypto::signature::Signer as _;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let nid = ctx.profile().public_key;
+        let signer = radicle::node::device::Device::mock_from_seed([0xff; 32]);
+        let repo = ctx
+            .profile()
+            .storage
+            .repository(RepoId::from_str(RID).unwrap())
+            .unwrap();
+        let app = super::router(ctx.clone(), HashMap::new(), GitBackend::default())
+            .layer(MockConnectInfo(SocketAddr::from(([0, 0, 0, 0], 8080))));
+        let target = format!("/{RID}.git/git-receive-pack");
+
+        // Push `HEAD` to a new branch. The repository has all its objects, so the pack is empty.
+        let zero = radicle::git::raw::Oid::zero();
+        let command = format!("{zero} {HEAD} refs/heads/signed\0report-status\n");
+        let mut body = format!("{:04x}{command}0000", command.len() + 4).into_bytes();
+        let mut pack = radicle::git::raw::Buf::new();
+        repo.backend
+            .packbuilder()
+            .unwrap()
+            .write_buf(&mut pack)
+            .unwrap();
+        body.extend_from_slice(&pack);
+
+        // A push to the given path, signed over the given headers for the receive-pack target.
+        let push = |path: &str, signed: &str, time: chrono::DateTime<chrono::Utc>| {
+            let date = time.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+            let message = signed
+                .split_whitespace()
+                .map(|name| match name {
+                    "(request-target)" => format!("{name}: post {target}"),
+                    "host" => format!("{name}: seed.radicle.xyz"),
+                    _ => format!("{name}: {date}"),
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            let signature: radicle::crypto::Signature = signer.sign(message.as_bytes());
+            let request = Request::builder()
+                .method("POST")
+                .uri(path)
+                .header("Host", "seed.radicle.xyz")
+                .header("Date", date)
+                .header(
+                    "Signature",
+                    format!(
+                        "keyId=\"{}\",algorithm=\"ed25519\",headers=\"{signed}\",signature=\"{}\"",
+                        radicle::identity::Did::from(nid),
+                        BASE64_STANDARD.encode(signature),
+                    ),
+                )
+                .header("Content-Type", "application/x-git-receive-pack-request")
+                .body(Body::from(body.clone()))
+                .unwrap();
+
+            app.clone().oneshot(request)
+        };
+        let headers = "(request-target) host date";
+        let now = chrono::Utc::now();
+
+        // Signatures are only valid for a few minutes.
+        let response = push(&target, headers, now - chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // Signatures have to cover the request, so that they can't be replayed.
+        let response = push(&target, "date", now).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = push(&format!("/{RID}.git/git-upload-pack"), headers, now)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = push(&target, headers, now).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response_body(response).await;
+        assert!(
+            body.windows(20).any(|w| w == b"ok refs/heads/signed"),
+            "{}",
+            String::from_utf8_lossy(&body)
+        );
+
+        let refname = radicle::git::RefString::try_from("refs/heads/signed").unwrap();
+        let refname = radicle::git::Qualified::from_refstr(&refname).unwrap();
+
+        assert_eq!(
+            repo.remote(&nid).unwrap().refs.get(&refname),
+            Some(HEAD.parse().unwrap()),
+            "pushed refs are signed"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_native_fetch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let token = test::create_session(&ctx);
+        let nid = ctx.profile().public_key;
+        let url = format!("{}/{RID}.git", serve(&ctx, GitBackend::Native).await);
+        let alice = tmp.path().join("alice");
+        let bob = tmp.path().join("bob");
+        let shallow = tmp.path().join("shallow");
+        let auth = format!("http.extraHeader=Authorization: Bearer {token}");
+        let v2 = ["-c", "protocol.version=2"];
+
+        let output = git(&alice, &[&v2[..], &["clone", &url, "."]].concat()).await;
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let output = git(&bob, &[&v2[..], &["clone", &url, "."]].concat()).await;
+        assert!(output.status.success());
+        assert_eq!(
+            git(&alice, &["rev-parse", "HEAD"]).await.stdout,
+            git(&bob, &["rev-parse", "HEAD"]).await.stdout
+        );
+
+        // Shallow clones are served by `git http-backend`.
+        let output = git(
+            &shallow,
+            &[&v2[..], &["clone", "--depth", "1", &url, "."]].concat(),
+        )
+        .await;
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        // Pushes are served by `git http-backend`.
+        assert!(git(
+            &alice,
+            &[
+                "-c",
+                "user.name=Alice",
+                "-c",
+                "user.email=alice@radicle.xyz",
+                "commit",
+                "--allow-empty",
+                "-m",
+                "Fetched natively"
+            ]
+        )
+        .await
+        .status
+        .success());
+        let output = git(
+            &alice,
+            &["-c", &auth, "push", "origin", "HEAD:refs/heads/feature"],
+        )
+        .await;
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        // Fetching on top of an existing clone negotiates the common commits.
+        let feature = format!("refs/namespaces/{nid}/refs/heads/feature");
+        let output = git(&bob, &[&v2[..], &["fetch", "origin", &feature]].concat()).await;
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(
+            git(&alice, &["rev-parse", "HEAD"]).await.stdout,
+            git(&bob, &["rev-parse", "FETCH_HEAD"]).await.stdout
+        );
+    }
+
+    #[test]
+    fn test_redacted_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", HeaderValue::from_static("Bearer token"));
+        headers.append("Cookie", HeaderValue::from_static("a=1"));
+        headers.append("Cookie", HeaderValue::from_static("b=2"));
+        headers.insert(
+            "Signature",
+            HeaderValue::from_static("keyId=\"key\",signature=\"sig\""),
+        );
+        headers.insert("Git-Protocol", HeaderValue::from_static("version=2"));
+
+        let logged = format!("{:?}", redacted(&headers));
+
+        for secret in ["token", "a=1", "b=2", "keyId"] {
+            assert!(!logged.contains(secret), "{logged}");
+        }
+        assert!(logged.contains("version=2"));
+        assert_eq!(redacted(&headers).get_all("cookie").iter().count(), 1);
+    }
 }
@@ -0,0 +1,528 @@
+//! In-process implementation of the `ls-refs` and `fetch` commands of the git protocol v2.
+//!
+//! See `gitprotocol-v2(5)`. Only the parts of the protocol needed to clone and fetch are
+//! implemented. Requests using other commands or arguments, as well as requests for older
+//! protocol versions and pushes, are left to `git http-backend`.
+use std::collections::HashSet;
+use std::{io, str};
+
+use axum::body::{Body, Bytes};
+use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use axum::http::{HeaderMap, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use futures_util::stream::{self, StreamExt as _, TryStreamExt as _};
+use tokio::sync::mpsc;
+
+use radicle::git::raw as git2;
+use radicle::identity::RepoId;
+use radicle::storage::ReadStorage;
+
+use crate::api::auth::Session;
+use crate::api::{Context, RADICLE_VERSION};
+use crate::error::GitError as Error;
+
+/// Max size of a request, once decompressed. Larger requests are served by `git http-backend`.
+const MAX_REQUEST_SIZE: usize = 16 * 1024 * 1024;
+/// Max size of a request that `git http-backend` buffers, once decompressed.
+pub const MAX_FALLBACK_REQUEST_SIZE: usize = 4 * MAX_REQUEST_SIZE;
+/// Max size of the data in a side-band packet.
+const MAX_SIDEBAND_DATA: usize = 65515;
+/// Number of side-band packets buffered while the client is reading the response.
+const PACK_BUFFER: usize = 16;
+
+/// Side-band carrying packfile data.
+const BAND_DATA: u8 = 1;
+/// Side-band carrying fatal errors.
+const BAND_ERROR: u8 = 3;
+
+/// Arguments of `fetch` that are handled natively. Arguments not listed here, such as
+/// `deepen` or `filter`, are handled by `git http-backend`.
+const FETCH_ARGS: [&str; 5] = [
+    "done",
+    "thin-pack",
+    "no-progress",
+    "include-tag",
+    "ofs-delta",
+];
+
+/// Result of serving a request natively.
+pub enum Served {
+    /// The request was served.
+    Response(Response),
+    /// The request isn't supported, and should be served by `git http-backend` instead.
+    /// Contains the original request body.
+    Fallback(Body),
+}
+
+/// A request body, read into memory.
+enum Read {
+    /// The whole body.
+    Complete(Bytes),
+    /// The body is larger than [`MAX_REQUEST_SIZE`]. Contains the body, including the part
+    /// that was already read, so that it can be served by `git http-backend` instead.
+    TooLarge(Body),
+}
+
+/// A protocol v2 command request.
+#[derive(Debug, Default)]
+struct Request {
+    /// The command, eg. `ls-refs`.
+    command: String,
+    /// Capabilities sent along with the command.
+    capabilities: Vec<String>,
+    /// Command arguments.
+    args: Vec<String>,
+}
+
+/// Serve a git request natively, if it is supported.
+pub async fn serve(
+    ctx: &Context,
+    rid: RepoId,
+    session: Option<&Session>,
+    method: &Method,
+    path: &str,
+    query: &str,
+    headers: &HeaderMap,
+    body: Body,
+) -> Result<Served, Error> {
+    let v2 = headers
+        .get_all("Git-Protocol")
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(':'))
+        .any(|p| p == "version=2");
+    if !v2 {
+        return Ok(Served::Fallback(body));
+    }
+
+    match (method, path) {
+        (&Method::GET, "info/refs") if query == "service=git-upload-pack" => {
+            super::identity(ctx, rid, session)?;
+
+            Ok(Served::Response(response(
+                "application/x-git-upload-pack-advertisement",
+                Body::from(advertisement()),
+            )))
+        }
+        (&Method::POST, "git-upload-pack") => {
+            super::identity(ctx, rid, session)?;
+
+            let raw = match read(body).await? {
+                Read::Complete(raw) => raw,
+                Read::TooLarge(body) => {
+                    tracing::debug!("git: falling back to git-http-backend for large request");
+                    return Ok(Served::Fallback(body));
+                }
+            };
+            let gzip = matches!(
+                headers.get("Content-Encoding").map(|h| h.to_str()),
+                Some(Ok("gzip"))
+            );
+            let decoded = match read(Body::from_stream(super::request_body(
+                Body::from(raw.clone()),
+                gzip,
+            )))
+            .await?
+            {
+                Read::Complete(decoded) => decoded,
+                Read::TooLarge(_) => {
+                    tracing::debug!("git: falling back to git-http-backend for large request");
+                    return Ok(Served::Fallback(Body::from(raw)));
+                }
+            };
+            let request = parse(&decoded)?;
+
+            if request.command == "ls-refs" && ls_refs_supported(&request) {
+                let ctx = ctx.clone();
+                let refs = blocking(move || ls_refs(&ctx, rid, &request.args)).await?;
+
+                Ok(Served::Response(upload_pack_result(Body::from(refs))))
+            } else if request.command == "fetch" && fetch_supported(&request) {
+                let body = fetch(ctx, rid, request).await?;
+
+                Ok(Served::Response(upload_pack_result(body)))
+            } else {
+                tracing::debug!(
+                    "git: falling back to git-http-backend for '{}' request",
+                    request.command
+                );
+                Ok(Served::Fallback(Body::from(raw)))
+            }
+        }
+        _ => Ok(Served::Fallback(body)),
+    }
+}
+
+/// The capability advertisement sent in response to `info/refs`.
+fn advertisement() -> Vec<u8> {
+    let mut out = Vec::new();
+
+    pkt_line(&mut out, "version 2\n");
+    pkt_line(
+        &mut out,
+        &format!("agent=radicle-httpd/{RADICLE_VERSION}\n"),
+    );
+    pkt_line(&mut out, "ls-refs\n");
+    // Shallow and filtered fetches are advertised, but handled by `git http-backend`.
+    pkt_line(&mut out, "fetch=shallow filter\n");
+    pkt_line(&mut out, "server-option\n");
+    pkt_line(&mut out, "object-format=sha1\n");
+    flush(&mut out);
+
+    out
+}
+
+/// Whether the capabilities sent along with a command are supported.
+fn capabilities_supported(request: &Request) -> bool {
+    request.capabilities.iter().all(|c| {
+        c.starts_with("agent=") || c.starts_with("server-option=") || c == "object-format=sha1"
+    })
+}
+
+/// Whether an `ls-refs` request can be served natively.
+fn ls_refs_supported(request: &Request) -> bool {
+    capabilities_supported(request)
+        && request
+            .args
+            .iter()
+            .all(|a| a == "symrefs" || a == "peel" || a.starts_with("ref-prefix "))
+}
+
+/// Whether a `fetch` request can be served natively.
+fn fetch_supported(request: &Request) -> bool {
+    capabilities_supported(request)
+        && request.args.iter().all(|a| {
+            a.starts_with("want ") || a.starts_with("have ") || FETCH_ARGS.contains(&a.as_str())
+        })
+}
+
+/// List the references of a repository.
+fn ls_refs(ctx: &Context, rid: RepoId, args: &[String]) -> Result<Vec<u8>, Error> {
+    let repo = ctx.profile().storage.repository(rid)?;
+    let repo = &repo.backend;
+    let symrefs = args.iter().any(|a| a == "symrefs");
+    let peel = args.iter().any(|a| a == "peel");
+    let prefixes = args
+        .iter()
+        .filter_map(|a| a.strip_prefix("ref-prefix "))
+        .collect::<Vec<_>>();
+
+    let mut names = repo
+        .references()?
+        .names()
+        .filter_map(|name| name.ok().map(ToOwned::to_owned))
+        .collect::<Vec<_>>();
+    names.sort();
+
+    let mut out = Vec::new();
+    for name in std::iter::once(String::from("HEAD")).chain(names) {
+        if !prefixes.is_empty() && !prefixes.iter().any(|p| name.starts_with(p)) {
+            continue;
+        }
+        let Ok(reference) = repo.find_reference(&name) else {
+            continue;
+        };
+        // Dangling symbolic references, eg. an unborn `HEAD`, are skipped.
+        let Ok(target) = reference.resolve() else {
+            continue;
+        };
+        let Some(oid) = target.target() else {
+            continue;
+        };
+        let mut line = format!("{oid} {name}");
+
+        if symrefs {
+            if let Some(target) = reference.symbolic_target() {
+                line.push_str(&format!(" symref-target:{target}"));
+            }
+        }
+        if peel {
+            if let Ok(tag) = repo.find_tag(oid) {
+                let peeled = tag.into_object().peel(git2::ObjectType::Any)?;
+                line.push_str(&format!(" peeled:{}", peeled.id()));
+            }
+        }
+        line.push('\n');
+        pkt_line(&mut out, &line);
+    }
+    flush(&mut out);
+
+    Ok(out)
+}
+
+/// Serve a `fetch` request.
+///
+/// Negotiation is stateless: if the client didn't send `done`, we acknowledge the objects we
+/// have in common, and send the packfile right away if there are any. Otherwise, the client
+/// continues the negotiation with more `have` lines.
+async fn fetch(ctx: &Context, rid: RepoId, request: Request) -> Result<Body, Error> {
+    let mut wants = Vec::new();
+    let mut haves = Vec::new();
+    for arg in &request.args {
+        if let Some(oid) = arg.strip_prefix("want ") {
+            wants.push(git2::Oid::from_str(oid).map_err(|_| Error::InvalidPktLine)?);
+        } else if let Some(oid) = arg.strip_prefix("have ") {
+            haves.push(git2::Oid::from_str(oid).map_err(|_| Error::InvalidPktLine)?);
+        }
+    }
+    let done = request.args.iter().any(|a| a == "done");
+    let include_tag = request.args.iter().any(|a| a == "include-tag");
+
+    let common = {
+        let ctx = ctx.clone();
+        let wants = wants.clone();
+
+        blocking(move || {
+            let repo = ctx.profile().storage.repository(rid)?;
+            let odb = repo.backend.odb()?;
+
+            if let Some(want) = wants.iter().find(|oid| !odb.exists(**oid)) {
+                return Ok(Err(want.to_owned()));
+            }
+            Ok(Ok(haves
+                .into_iter()
+                .filter(|oid| odb.exists(*oid))
+                .collect::<Vec<_>>()))
+        })
+        .await?
+    };
+    let common = match common {
+        Ok(common) => common,
+        Err(want) => {
+            let mut out = Vec::new();
+            pkt_line(&mut out, &format!("ERR upload-pack: not our ref {want}\n"));
+
+            return Ok(Body::from(out));
+        }
+    };
+
+    let mut prelude = Vec::new();
+    if !done {
+        pkt_line(&mut prelude, "acknowledgments\n");
+        if common.is_empty() {
+            pkt_line(&mut prelude, "NAK\n");
+            flush(&mut prelude);
+
+            return Ok(Body::from(prelude));
+        }
+        for oid in &common {
+            pkt_line(&mut prelude, &format!("ACK {oid}\n"));
+        }
+        pkt_line(&mut prelude, "ready\n");
+        delim(&mut prelude);
+    }
+    pkt_line(&mut prelude, "packfile\n");
+
+    let (tx, rx) = mpsc::channel::<Bytes>(PACK_BUFFER);
+    let ctx = ctx.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let result = pack(&ctx, rid, &wants, &common, include_tag, |data| {
+            data.chunks(MAX_SIDEBAND_DATA)
+                .all(|chunk| tx.blocking_send(sideband(BAND_DATA, chunk)).is_ok())
+        });
+        let end = match result {
+            Ok(()) => {
+                let mut out = Vec::new();
+                flush(&mut out);
+                out
+            }
+            Err(err) => {
+                tracing::error!("git: error sending packfile for {rid}: {err}");
+                sideband(BAND_ERROR, format!("error: {err}\n").as_bytes()).into()
+            }
+        };
+        tx.blocking_send(Bytes::from(end)).ok();
+    });
+
+    let pack = stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (Ok::<_, io::Error>(chunk), rx))
+    });
+    Ok(Body::from_stream(
+        stream::once(async { Ok(Bytes::from(prelude)) }).chain(pack),
+    ))
+}
+
+/// Build a packfile with the objects reachable from `wants` but not from `common`, passing
+/// it to `write` in chunks. Stops early if `write` returns `false`.
+fn pack(
+    ctx: &Context,
+    rid: RepoId,
+    wants: &[git2::Oid],
+    common: &[git2::Oid],
+    include_tag: bool,
+    mut write: impl FnMut(&[u8]) -> bool,
+) -> Result<(), git2::Error> {
+    let repo = ctx
+        .profile()
+        .storage
+        .repository(rid)
+        .map_err(|e| git2::Error::from_str(&e.to_string()))?;
+    let repo = &repo.backend;
+    let mut builder = repo.packbuilder()?;
+    let mut walk = repo.revwalk()?;
+
+    for want in wants {
+        let mut object = repo.find_object(*want, None)?;
+
+        // Annotated tags are sent along with the objects they point to.
+        while let Some(tag) = object.as_tag() {
+            builder.insert_object(tag.id(), None)?;
+            object = tag.target()?;
+        }
+        match object.kind() {
+            Some(git2::ObjectType::Commit) => walk.push(object.id())?,
+            Some(git2::ObjectType::Tree) => builder.insert_tree(object.id())?,
+            _ => builder.insert_object(object.id(), None)?,
+        }
+    }
+    for oid in common {
+        if repo.find_commit(*oid).is_ok() {
+            walk.hide(*oid)?;
+        }
+    }
+
+    if include_tag {
+        let mut commits = HashSet::new();
+        let mut tags = repo.revwalk()?;
+        for want in wants {
+            if let Ok(commit) = repo.find_object(*want, None)?.peel_to_commit() {
+                tags.push(commit.id())?;
+            }
+        }
+        for oid in common {
+            if repo.find_commit(*oid).is_ok() {
+                tags.hide(*oid)?;
+            }
+        }
+        for oid in tags {
+            commits.insert(oid?);
+        }
+        for reference in repo.references()? {
+            let Some(oid) = reference?.target() else {
+                continue;
+            };
+            let Ok(tag) = repo.find_tag(oid) else {
+                continue;
+            };
+            if tag
+                .target()
+                .and_then(|t| t.peel_to_commit())
+                .is_ok_and(|c| commits.contains(&c.id()))
+            {
+                builder.insert_object(oid, None)?;
+            }
+        }
+    }
+    builder.insert_walk(&mut walk)?;
+    builder.foreach(|data| write(data))
+}
+
+/// Read a request body into memory, unless it is larger than [`MAX_REQUEST_SIZE`].
+async fn read(body: Body) -> Result<Read, Error> {
+    let mut out = Vec::new();
+    let mut stream = body.into_data_stream().map_err(io::Error::other);
+
+    while let Some(chunk) = stream.next().await {
+        out.extend_from_slice(&chunk?);
+        if out.len() > MAX_REQUEST_SIZE {
+            let read = stream::once(async move { Ok(Bytes::from(out)) });
+
+            return Ok(Read::TooLarge(Body::from_stream(read.chain(stream))));
+        }
+    }
+    Ok(Read::Complete(Bytes::from(out)))
+}
+
+/// Parse a protocol v2 command request.
+fn parse(mut data: &[u8]) -> Result<Request, Error> {
+    let mut request = Request::default();
+    let mut args = false;
+
+    loop {
+        let len = data
+            .get(..4)
+            .and_then(|l| str::from_utf8(l).ok())
+            .and_then(|l| usize::from_str_radix(l, 16).ok())
+            .ok_or(Error::InvalidPktLine)?;
+
+        match len {
+            // Flush-pkt, marking the end of the request.
+            0 => break,
+            // Delim-pkt, separating capabilities from arguments.
+            1 => {
+                args = true;
+                data = &data[4..];
+                continue;
+            }
+            2..=4 => return Err(Error::InvalidPktLine),
+            _ => {}
+        }
+        let line = data.get(4..len).ok_or(Error::InvalidPktLine)?;
+        let line = str::from_utf8(line).map_err(|_| Error::InvalidPktLine)?;
+        let line = line.strip_suffix('\n').unwrap_or(line).to_owned();
+        data = &data[len..];
+
+        if args {
+            request.args.push(line);
+        } else if let Some(command) = line.strip_prefix("command=") {
+            request.command = command.to_owned();
+        } else {
+            request.capabilities.push(line);
+        }
+    }
+    Ok(request)
+}
+
+/// Run a blocking repository operation.
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, Error> + Send + 'static,
+) -> Result<T, Error> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(io::Error::other)?
+}
+
+/// Build a response with the headers `git http-backend` would send.
+fn response(content_type: &'static str, body: Body) -> Response {
+    (
+        StatusCode::OK,
+        [
+            (CONTENT_TYPE, content_type),
+            (CACHE_CONTROL, "no-cache, max-age=0, must-revalidate"),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// Build the response to an `upload-pack` command.
+fn upload_pack_result(body: Body) -> Response {
+    response("application/x-git-upload-pack-result", body)
+}
+
+/// Append a pkt-line.
+fn pkt_line(out: &mut Vec<u8>, line: &str) {
+    out.extend_from_slice(format!("{:04x}", line.len() + 4).as_bytes());
+    out.extend_from_slice(line.as_bytes());
+}
+
+/// Append a flush-pkt.
+fn flush(out: &mut Vec<u8>) {
+    out.extend_from_slice(b"0000");
+}
+
+/// Append a delim-pkt.
+fn delim(out: &mut Vec<u8>) {
+    out.extend_from_slice(b"0001");
+}
+
+/// Build a side-band pkt-line.
+fn sideband(band: u8, data: &[u8]) -> Bytes {
+    let mut out = Vec::with_capacity(data.len() + 5);
+    out.extend_from_slice(format!("{:04x}", data.len() + 5).as_bytes());
+    out.push(band);
+    out.extend_from_slice(data);
+
+    Bytes::from(out)
+}
@@ -6,8 +6,9 @@ pub mod error;
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::num::NonZeroUsize;
+use std::path::PathBuf;
 use std::process::Command;
-use std::str;
+use std::str::{self, FromStr};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -17,7 +18,7 @@ use axum::http::{Request, Response};
 use axum::response::IntoResponse;
 use axum::routing::get;
 use axum::{middleware, Json, Router};
-use hyper::header::CONTENT_TYPE;
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE, ETAG, IF_NONE_MATCH, LINK};
 use hyper::Method;
 use tokio::net::TcpListener;
 use tower_http::cors;
@@ -41,25 +42,61 @@ mod raw;
 mod test;
 mod tracing_extra;
 
+pub use cache::Endpoint as CacheEndpoint;
+
 /// Default cache HTTP size.
 pub const DEFAULT_CACHE_SIZE: NonZeroUsize = NonZeroUsize::new(100).unwrap();
+/// Default max size of the on-disk cache, in bytes.
+pub const DEFAULT_CACHE_DIR_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// How git clones and fetches are served.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GitBackend {
+    /// Proxy all requests to `git http-backend`.
+    #[default]
+    HttpBackend,
+    /// Serve protocol v2 `ls-refs` and `fetch` requests in-process, and proxy all other
+    /// requests to `git http-backend`.
+    Native,
+}
+
+impl FromStr for GitBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "http-backend" => Ok(Self::HttpBackend),
+            "native" => Ok(Self::Native),
+            _ => Err(format!("unknown git backend '{s}'")),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Options {
     pub aliases: HashMap<String, RepoId>,
     pub listen: SocketAddr,
     pub cache: Option<NonZeroUsize>,
+    /// Store cached responses in this directory instead of in memory, so that they persist
+    /// across restarts.
+    pub cache_dir: Option<PathBuf>,
+    /// Max size of the on-disk cache, in bytes.
+    pub cache_dir_size: u64,
+    /// Endpoints whose responses are cached.
+    pub cache_endpoints: Vec<CacheEndpoint>,
+    pub git_backend: GitBackend,
 }
 
 /// Run the Server.
 pub async fn run(options: Options) -> anyhow::Result<()> {
-    let git_version = Command::new("git")
-        .arg("version")
-        .output()
-        .context("'git' command must be available")?
-        .stdout;
-
-    tracing::info!("{}", str::from_utf8(&git_version)?.trim());
+    match Command::new("git").arg("version").output() {
+        Ok(output) => tracing::info!("{}", str::from_utf8(&output.stdout)?.trim()),
+        // The native backend only needs `git` for pushes, and fetches it can't serve.
+        Err(err) if options.git_backend == GitBackend::Native => {
+            tracing::warn!("'git' command is not available: {err}");
+        }
+        Err(err) => return Err(err).context("'git' command must be available"),
+    }
 
     let listener = TcpListener::bind(options.listen).await?;
 
@@ -114,24 +151,31 @@ pub async fn run(options: Options) -> anyhow::Result<()> {
 
 /// Create a router consisting of other sub-routers.
 fn router(options: Options, profile: Profile) -> anyhow::Result<Router> {
-    let profile = Arc::new(profile);
-    let ctx = api::Context::new(profile.clone(), &options);
+    let ctx = api::Context::new(Arc::new(profile), &options)?;
 
+    let git_router = git::router(ctx.clone(), options.aliases, options.git_backend);
+    let raw_router = raw::router(ctx.clone());
     let api_router = api::router(ctx);
-    let git_router = git::router(profile.clone(), options.aliases);
-    let raw_router = raw::router(profile);
 
     let app = Router::new()
         .route("/", get(root_index_handler))
         .merge(git_router)
         .nest("/api", api_router)
         .nest("/raw", raw_router)
+        .layer(middleware::from_fn(axum_extra::private_cache_middleware))
         .layer(
             CorsLayer::new()
                 .max_age(Duration::from_secs(86400))
                 .allow_origin(cors::Any)
-                .allow_methods([Method::GET])
-                .allow_headers([CONTENT_TYPE]),
+                .allow_methods([
+                    Method::GET,
+                    Method::POST,
+                    Method::PATCH,
+                    Method::PUT,
+                    Method::DELETE,
+                ])
+                .allow_headers([CONTENT_TYPE, AUTHORIZATION, IF_NONE_MATCH])
+                .expose_headers([ETAG, LINK]),
         );
 
     Ok(app)
@@ -218,6 +262,10 @@ mod routes {
                 aliases: HashMap::new(),
                 listen: SocketAddr::from(([0, 0, 0, 0], 8080)),
                 cache: None,
+                cache_dir: None,
+                cache_dir_size: super::DEFAULT_CACHE_DIR_SIZE,
+                cache_endpoints: super::CacheEndpoint::ALL.to_vec(),
+                git_backend: super::GitBackend::default(),
             },
             test::profile(tmp.path(), [0xff; 32]),
         )
@@ -22,7 +22,14 @@ Options
     --listen       <address>         Address to listen on (default: 0.0.0.0:8080)
     --alias, -a    <alias> <rid>     Provide alias and RID pairs to shorten git clone commands for repositories,
                                      e.g. heartwood and rad:z3gqcJUoA1n9HaHKufZs5FCSGazv5 to produce https://seed.radicle.xyz/heartwood.git
-    --cache        <number>          Max amount of items in cache for /tree endpoints (default: 100)
+    --cache        <number>          Max amount of items in the in-memory cache for immutable endpoints (default: 100)
+    --cache-dir    <path>            Cache responses on disk in the given directory, instead of in memory
+    --cache-dir-size <mib>           Max size of the on-disk cache, in MiB (default: 1024)
+    --cache-endpoints <endpoint>,..  Endpoints to cache, out of: commit, diff, tree, tree-stats, blob, readme,
+                                     blame, interdiff
+                                     (default: all)
+    --git-backend  <backend>         Serve git fetches with `git http-backend`, or natively where possible,
+                                     one of: http-backend, native (default: http-backend)
     --version, -v                    Print program version
     --help, -h                       Print help
 "#;
@@ -54,6 +61,10 @@ fn parse_options() -> Result<httpd::Options, lexopt::Error> {
     let mut listen = None;
     let mut aliases = HashMap::new();
     let mut cache = Some(httpd::DEFAULT_CACHE_SIZE);
+    let mut cache_dir = None;
+    let mut cache_dir_size = httpd::DEFAULT_CACHE_DIR_SIZE;
+    let mut cache_endpoints = httpd::CacheEndpoint::ALL.to_vec();
+    let mut git_backend = httpd::GitBackend::default();
 
     while let Some(arg) = parser.next()? {
         match arg {
@@ -78,6 +89,28 @@ fn parse_options() -> Result<httpd::Options, lexopt::Error> {
                 let size = parser.value()?.parse()?;
                 cache = NonZeroUsize::new(size);
             }
+            Long("cache-dir") => {
+                cache_dir = Some(parser.value()?.into());
+            }
+            Long("cache-dir-size") => {
+                cache_dir_size = parser.value()?.parse_with(|s| {
+                    s.parse::<u64>()
+                        .map_err(|e| e.to_string())?
+                        .checked_mul(1024 * 1024)
+                        .ok_or_else(|| String::from("size is too large"))
+                })?;
+            }
+            Long("cache-endpoints") => {
+                cache_endpoints = parser
+                    .value()?
+                    .string()?
+                    .split(',')
+                    .map(|e| e.trim().parse())
+                    .collect::<Result<_, _>>()?;
+            }
+            Long("git-backend") => {
+                git_backend = parser.value()?.parse()?;
+            }
             Long("help") | Short('h') => {
                 println!("{HELP_MSG}");
                 process::exit(0);
@@ -89,5 +122,9 @@ fn parse_options() -> Result<httpd::Options, lexopt::Error> {
         aliases,
         listen: listen.unwrap_or_else(|| ([0, 0, 0, 0], 8080).into()),
         cache,
+        cache_dir,
+        cache_dir_size,
+        cache_endpoints,
+        git_backend,
     })
 }
@@ -1,73 +1,129 @@
-use std::process::Command;
+use std::process::Stdio;
 use std::str::FromStr;
-use std::sync::Arc;
 
+use async_compression::tokio::bufread::{GzipEncoder, XzEncoder, ZstdEncoder};
+use axum::body::Body;
 use axum::extract::{Query, State};
 use axum::http::{header, HeaderValue, StatusCode};
 use axum::response::IntoResponse;
 use axum::routing::get;
 use axum::Router;
+use futures_util::stream::{self, StreamExt as _, TryStreamExt as _};
 use hyper::HeaderMap;
 use radicle_surf::blob::{Blob, BlobRef};
+use tokio::io::{AsyncRead, AsyncReadExt as _, BufReader};
+use tokio::process::Command;
+use tokio_util::io::ReaderStream;
 
 use radicle::git::Oid;
 use radicle::prelude::RepoId;
-use radicle::profile::Profile;
 use radicle::storage::{ReadRepository, ReadStorage};
 use radicle_surf::Repository;
 
-use crate::api::query::RawQuery;
+use crate::api::auth::{self, Session};
+use crate::api::query::{ArchiveQuery, RawQuery};
+use crate::api::Context;
 use crate::axum_extra::Path;
 use crate::error::RawError as Error;
 
 const MAX_BLOB_SIZE: usize = 10_485_760;
 
-const ARCHIVE_SUFFIX: &str = ".tar.gz";
+/// Archive formats, selected by file suffix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveFormat {
+    Tar,
+    TarGz,
+    TarZst,
+    TarXz,
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Format of archives requested without a suffix.
+    const DEFAULT: Self = Self::TarGz;
+    const ALL: [Self; 5] = [Self::Tar, Self::TarGz, Self::TarZst, Self::TarXz, Self::Zip];
+
+    fn suffix(&self) -> &'static str {
+        match self {
+            Self::Tar => ".tar",
+            Self::TarGz => ".tar.gz",
+            Self::TarZst => ".tar.zst",
+            Self::TarXz => ".tar.xz",
+            Self::Zip => ".zip",
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            Self::Tar => "application/x-tar",
+            Self::TarGz => "application/gzip",
+            Self::TarZst => "application/zstd",
+            Self::TarXz => "application/x-xz",
+            Self::Zip => "application/zip",
+        }
+    }
 
-pub fn router(profile: Arc<Profile>) -> Router {
+    /// Split a file name into its stem and archive format, if it has a known suffix.
+    fn split(name: &str) -> Option<(&str, Self)> {
+        Self::ALL
+            .into_iter()
+            .find_map(|f| name.strip_suffix(f.suffix()).map(|stem| (stem, f)))
+    }
+}
+
+pub fn router(ctx: Context) -> Router {
     Router::new()
         .route("/:rid/:sha", get(commit_handler))
         .route("/:rid/:sha/*path", get(file_by_commit_handler))
         .route("/:rid/head/*path", get(file_by_canonical_head_handler))
         .route("/:rid/archive/*refname", get(archive_by_refname_handler))
         .route("/:rid/blobs/:oid", get(file_by_oid_handler))
-        .with_state(profile)
+        .with_state(ctx)
 }
 
 async fn commit_handler(
     Path((rid, sha)): Path<(RepoId, String)>,
-    State(profile): State<Arc<Profile>>,
-) -> Result<(StatusCode, HeaderMap, Vec<u8>), Error> {
-    let storage = &profile.storage;
+    State(ctx): State<Context>,
+    session: Option<Session>,
+    Query(qs): Query<ArchiveQuery>,
+) -> Result<(StatusCode, HeaderMap, Body), Error> {
+    let storage = &ctx.profile().storage;
     let repo = storage.repository(rid)?;
 
-    // Don't allow accessing private repos.
-    if repo.identity_doc()?.visibility().is_private() {
+    // Only allow accessing private repos with a session they are visible to.
+    if !auth::can_view(&repo.identity_doc()?.doc, session.as_ref()) {
         return Err(Error::NotFound);
     }
 
-    if !sha.ends_with(ARCHIVE_SUFFIX) {
+    let Some((sha, format)) = ArchiveFormat::split(&sha) else {
         return Err(Error::NotFound);
-    }
-
-    let sha = sha.trim_end_matches(ARCHIVE_SUFFIX);
+    };
 
     if Oid::from_str(sha).is_err() {
         return Err(Error::NotFound);
     }
 
-    archive_by_refname(rid, sha.to_string(), profile).await
+    archive_by_refname(
+        rid,
+        sha.to_string(),
+        format,
+        qs.path,
+        &ctx,
+        session.as_ref(),
+    )
+    .await
 }
 
 async fn file_by_commit_handler(
     Path((rid, sha, path)): Path<(RepoId, Oid, String)>,
-    State(profile): State<Arc<Profile>>,
+    State(ctx): State<Context>,
+    session: Option<Session>,
 ) -> impl IntoResponse {
-    let storage = &profile.storage;
+    let storage = &ctx.profile().storage;
     let repo = storage.repository(rid)?;
 
-    // Don't allow downloading raw files for private repos.
-    if repo.identity_doc()?.visibility().is_private() {
+    // Only allow downloading raw files of private repos with a session they are visible to.
+    if !auth::can_view(&repo.identity_doc()?.doc, session.as_ref()) {
         return Err(Error::NotFound);
     }
 
@@ -79,21 +135,33 @@ async fn file_by_commit_handler(
 
 async fn archive_by_refname_handler(
     Path((rid, refname)): Path<(RepoId, String)>,
-    State(profile): State<Arc<Profile>>,
-) -> Result<(StatusCode, HeaderMap, Vec<u8>), Error> {
-    archive_by_refname(rid, refname, profile).await
+    State(ctx): State<Context>,
+    session: Option<Session>,
+    Query(qs): Query<ArchiveQuery>,
+) -> Result<(StatusCode, HeaderMap, Body), Error> {
+    // Archives requested without a suffix default to `.tar.gz`.
+    let (refname, format) = match ArchiveFormat::split(&refname) {
+        Some((refname, format)) => (refname.to_owned(), format),
+        None => (refname, ArchiveFormat::DEFAULT),
+    };
+
+    archive_by_refname(rid, refname, format, qs.path, &ctx, session.as_ref()).await
 }
 
+/// Stream an archive of a commit, optionally limited to a subdirectory.
 async fn archive_by_refname(
     rid: RepoId,
     refname: String,
-    profile: Arc<Profile>,
-) -> Result<(StatusCode, HeaderMap, Vec<u8>), Error> {
-    let storage = &profile.storage;
+    format: ArchiveFormat,
+    path: Option<String>,
+    ctx: &Context,
+    session: Option<&Session>,
+) -> Result<(StatusCode, HeaderMap, Body), Error> {
+    let storage = &ctx.profile().storage;
     let repo = storage.repository(rid)?;
 
-    // Don't allow downloading tarballs for private repos.
-    if repo.identity_doc()?.visibility().is_private() {
+    // Only allow downloading tarballs of private repos with a session they are visible to.
+    if !auth::can_view(&repo.identity_doc()?.doc, session) {
         return Err(Error::NotFound);
     }
 
@@ -104,34 +172,88 @@ async fn archive_by_refname(
     let (oid, via_refname) = match Oid::from_str(&refname) {
         Ok(oid) => (oid, false),
         Err(_) => (
+            // Annotated tags point to a tag object, rather than to a commit.
             repo.backend
-                .resolve_reference_from_short_name(&refname)
-                .map(|reference| reference.target())?
-                .ok_or(Error::NotFound)?
+                .resolve_reference_from_short_name(&refname)?
+                .peel_to_commit()
+                .map_err(|_| Error::NotFound)?
+                .id()
                 .into(),
             true,
         ),
     };
 
-    let output = Command::new("git")
-        .arg("archive")
-        .arg("--format=tar.gz")
-        .arg(oid.to_string())
-        .current_dir(repo.path())
-        .output()?;
+    let path = path
+        .map(|p| p.trim_matches('/').to_owned())
+        .filter(|p| !p.is_empty());
+    let commit = repo
+        .backend
+        .find_commit(oid.into())
+        .map_err(|_| Error::NotFound)?;
+    if let Some(path) = &path {
+        let tree = commit.tree()?;
+        let entry = tree
+            .get_path(std::path::Path::new(path))
+            .map_err(|_| Error::NotFound)?;
+
+        if entry.kind() != Some(radicle::git::raw::ObjectType::Tree) {
+            return Err(Error::NotFound);
+        }
+    }
 
-    if !output.status.success() {
-        return Err(Error::Archive(
-            output.status,
-            String::from_utf8_lossy(&output.stderr).to_string(),
-        ));
+    let mut cmd = Command::new("git");
+    cmd.arg("archive")
+        .arg(match format {
+            ArchiveFormat::Zip => "--format=zip",
+            _ => "--format=tar",
+        })
+        .arg(oid.to_string());
+    if let Some(path) = &path {
+        cmd.arg("--").arg(path);
     }
+    let mut child = cmd
+        .current_dir(repo.path())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // These are safe because we captured the child's stdio.
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut stderr = child.stderr.take().unwrap();
+    let stderr = tokio::spawn(async move {
+        let mut output = Vec::new();
+        stderr.read_to_end(&mut output).await.ok();
+        output
+    });
+
+    // Compress the archive as it is produced by `git archive`.
+    let archive: Box<dyn AsyncRead + Send + Unpin> = match format {
+        ArchiveFormat::Tar | ArchiveFormat::Zip => Box::new(stdout),
+        ArchiveFormat::TarGz => Box::new(GzipEncoder::new(stdout)),
+        ArchiveFormat::TarZst => Box::new(ZstdEncoder::new(stdout)),
+        ArchiveFormat::TarXz => Box::new(XzEncoder::new(stdout)),
+    };
+    // Fail the response body if `git archive` fails, so that clients don't mistake a
+    // truncated archive for a complete one.
+    let exit = stream::once(async move {
+        let status = child.wait().await?;
+        if status.success() {
+            return Ok(None);
+        }
+        let stderr = stderr.await.unwrap_or_default();
+        let err = Error::Archive(status, String::from_utf8_lossy(&stderr).to_string());
+        tracing::error!("{err}");
+
+        Err(std::io::Error::other(err.to_string()))
+    })
+    .try_filter_map(|chunk| async move { Ok(chunk) });
+    let body = Body::from_stream(ReaderStream::new(archive).chain(exit));
 
     // Build a filename for the archive, which includes the
     // refname (if one was given):
     //
-    // Without refname:   <repo-name>-<oid>.tar.gz
-    // With    refname:   <repo-name>-<refname>--<oid>.tar.gz
+    // Without refname:   <repo-name>-<oid><suffix>
+    // With    refname:   <repo-name>-<refname>--<oid><suffix>
     let filename = {
         let mut build = String::from(repo_name);
         build.push('-');
@@ -144,28 +266,32 @@ async fn archive_by_refname(
         }
 
         build.push_str(oid.to_string().as_str());
-        build.push_str(ARCHIVE_SUFFIX);
+        build.push_str(format.suffix());
         build
     };
 
     let mut response_headers = HeaderMap::new();
-    response_headers.insert("Content-Type", HeaderValue::from_str("application/gzip")?);
+    response_headers.insert(
+        "Content-Type",
+        HeaderValue::from_static(format.content_type()),
+    );
     response_headers.insert(
         "Content-Disposition",
         HeaderValue::from_str(&format!("attachment; filename=\"{filename}\""))?,
     );
-    Ok::<_, Error>((StatusCode::OK, response_headers, output.stdout))
+    Ok::<_, Error>((StatusCode::OK, response_headers, body))
 }
 
 async fn file_by_canonical_head_handler(
     Path((rid, path)): Path<(RepoId, String)>,
-    State(profile): State<Arc<Profile>>,
+    State(ctx): State<Context>,
+    session: Option<Session>,
 ) -> impl IntoResponse {
-    let storage = &profile.storage;
+    let storage = &ctx.profile().storage;
     let repo = storage.repository(rid)?;
 
-    // Don't allow downloading raw files for private repos.
-    if repo.identity_doc()?.visibility().is_private() {
+    // Only allow downloading raw files of private repos with a session they are visible to.
+    if !auth::can_view(&repo.identity_doc()?.doc, session.as_ref()) {
         return Err(Error::NotFound);
     }
 
@@ -195,14 +321,15 @@ fn blob_response(blob: Blob<BlobRef>) -> Result<(StatusCode, HeaderMap, Vec<u8>)
 
 async fn file_by_oid_handler(
     Path((rid, oid)): Path<(RepoId, Oid)>,
-    State(profile): State<Arc<Profile>>,
+    State(ctx): State<Context>,
+    session: Option<Session>,
     Query(_qs): Query<RawQuery>,
 ) -> impl IntoResponse {
-    let storage = &profile.storage;
+    let storage = &ctx.profile().storage;
     let repo = storage.repository(rid)?;
 
-    // Don't allow downloading raw files for private repos.
-    if repo.identity_doc()?.visibility().is_private() {
+    // Only allow downloading raw files of private repos with a session they are visible to.
+    if !auth::can_view(&repo.identity_doc()?.doc, session.as_ref()) {
         return Err(Error::NotFound);
     }
 
@@ -227,14 +354,16 @@ async fn file_by_oid_handler(
 mod routes {
     use axum::http::StatusCode;
 
-    use crate::test::{self, get, RID, RID_PRIVATE};
+    use std::io::Read as _;
+
+    use crate::test::{self, get, get_auth, HEAD, RID, RID_PRIVATE};
     use radicle::storage::ReadStorage;
 
     #[tokio::test]
     async fn test_file_handler() {
         let tmp = tempfile::tempdir().unwrap();
         let ctx = test::seed(tmp.path());
-        let app = super::router(ctx.profile().to_owned());
+        let app = super::router(ctx.clone());
 
         let response = get(&app, format!("/{RID}/head/dir1/README")).await;
 
@@ -250,4 +379,74 @@ mod routes {
         let response = get(&app, format!("/{RID_PRIVATE}/head/README")).await;
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
+
+    #[tokio::test]
+    async fn test_file_handler_private() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let stranger = radicle::node::device::Device::mock_from_seed([0xaa; 32]);
+        let stranger = test::create_session_for(&ctx, *stranger.public_key(), "stranger");
+        let delegate = test::create_session(&ctx);
+
+        let response = get_auth(&app, format!("/{RID_PRIVATE}/head/README"), stranger).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = get_auth(&app, format!("/{RID_PRIVATE}/head/README"), delegate).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body().await, "Hello Private World!\n");
+    }
+
+    #[tokio::test]
+    async fn test_archive() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let app = super::router(ctx.clone());
+
+        // Archives requested without a suffix are `.tar.gz`.
+        let response = get(&app, format!("/{RID}/archive/master")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["Content-Type"], "application/gzip");
+        assert_eq!(
+            response.headers()["Content-Disposition"],
+            format!("attachment; filename=\"hello-world-master-{HEAD}.tar.gz\"")
+        );
+        let mut tar = Vec::new();
+        flate2::read::GzDecoder::new(&response.body().await[..])
+            .read_to_end(&mut tar)
+            .unwrap();
+        let tar = String::from_utf8_lossy(&tar);
+        assert!(tar.contains("Hello World!"));
+        assert!(tar.contains("Hello World from dir1!"));
+
+        let response = get(&app, format!("/{RID}/{HEAD}.zip")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["Content-Type"], "application/zip");
+        assert_eq!(
+            response.headers()["Content-Disposition"],
+            format!("attachment; filename=\"hello-world-{HEAD}.zip\"")
+        );
+        assert!(response.body().await.starts_with(b"PK"));
+
+        for suffix in [".tar.zst", ".tar.xz"] {
+            let response = get(&app, format!("/{RID}/archive/master{suffix}")).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert!(!response.body().await.is_empty());
+        }
+
+        // Archives can be limited to a subdirectory.
+        let response = get(&app, format!("/{RID}/archive/master.tar?path=dir1")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["Content-Type"], "application/x-tar");
+        let tar = response.body().await;
+        let tar = String::from_utf8_lossy(&tar);
+        assert!(tar.contains("dir1/README"));
+        assert!(!tar.contains("Hello World!"));
+
+        let response = get(&app, format!("/{RID}/archive/master.tar?path=dir2")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = get(&app, format!("/{RID_PRIVATE}/archive/master")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
 }
@@ -14,7 +14,7 @@ use radicle::cob::migrate;
 use radicle::cob::patch::MergeTarget;
 use radicle::crypto::signature::Signer;
 use radicle::crypto::ssh::Keystore;
-use radicle::crypto::{KeyPair, Seed, Signature};
+use radicle::crypto::{KeyPair, PublicKey, Seed, Signature};
 use radicle::git::{raw as git2, RefString};
 use radicle::identity::{project, Visibility};
 use radicle::node::device::Device;
@@ -24,6 +24,7 @@ use radicle::storage::ReadStorage;
 use radicle::{node, profile};
 use radicle::{Node, Storage};
 
+use crate::api::auth::Session;
 use crate::api::Context;
 
 pub const RID: &str = "rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp";
@@ -328,25 +329,132 @@ fn seed_with_signer<G: Signer<Signature>>(
         aliases: std::collections::HashMap::new(),
         listen: std::net::SocketAddr::from(([0, 0, 0, 0], 8080)),
         cache: Some(crate::DEFAULT_CACHE_SIZE),
+        cache_dir: None,
+        cache_dir_size: crate::DEFAULT_CACHE_DIR_SIZE,
+        cache_endpoints: crate::CacheEndpoint::ALL.to_vec(),
+        git_backend: crate::GitBackend::default(),
     };
 
-    Context::new(Arc::new(profile), &options)
+    Context::new(Arc::new(profile), &options).unwrap()
+}
+
+/// Register an authenticated session for the seed's key, returning its bearer token.
+pub fn create_session(ctx: &Context) -> String {
+    create_session_for(
+        ctx,
+        ctx.profile().public_key,
+        "u9MGAkkfkMOv0uDDB2WeUHBT7HbsO2Dy",
+    )
+}
+
+/// Register an authenticated session for the given key, returning its bearer token.
+pub fn create_session_for(ctx: &Context, public_key: PublicKey, token: &str) -> String {
+    let now = chrono::Utc::now().timestamp();
+
+    ctx.sessions().insert(
+        token.to_owned(),
+        Session {
+            id: format!("session-{token}"),
+            public_key,
+            issued_at: now,
+            expires_at: now + 3600,
+        },
+    );
+    token.to_owned()
 }
 
 pub async fn get(app: &Router, path: impl ToString) -> Response {
     Response(
         app.clone()
-            .oneshot(request(path, Method::GET, None))
+            .oneshot(request(path, Method::GET, None, None))
+            .await
+            .unwrap(),
+    )
+}
+
+pub async fn get_auth(app: &Router, path: impl ToString, auth: String) -> Response {
+    Response(
+        app.clone()
+            .oneshot(request(path, Method::GET, None, Some(auth)))
             .await
             .unwrap(),
     )
 }
 
-fn request(path: impl ToString, method: Method, body: Option<Body>) -> Request<Body> {
-    let request = Request::builder()
+/// Make a conditional `GET` request, with an `If-None-Match` header.
+pub async fn get_if_none_match(app: &Router, path: impl ToString, etag: &str) -> Response {
+    let mut request = request(path, Method::GET, None, None);
+    request
+        .headers_mut()
+        .insert("If-None-Match", etag.parse().unwrap());
+
+    Response(app.clone().oneshot(request).await.unwrap())
+}
+
+pub async fn post(
+    app: &Router,
+    path: impl ToString,
+    body: Option<Body>,
+    auth: Option<String>,
+) -> Response {
+    Response(
+        app.clone()
+            .oneshot(request(path, Method::POST, body, auth))
+            .await
+            .unwrap(),
+    )
+}
+
+pub async fn patch(
+    app: &Router,
+    path: impl ToString,
+    body: Option<Body>,
+    auth: Option<String>,
+) -> Response {
+    Response(
+        app.clone()
+            .oneshot(request(path, Method::PATCH, body, auth))
+            .await
+            .unwrap(),
+    )
+}
+
+pub async fn put(
+    app: &Router,
+    path: impl ToString,
+    body: Option<Body>,
+    auth: Option<String>,
+) -> Response {
+    Response(
+        app.clone()
+            .oneshot(request(path, Method::PUT, body, auth))
+            .await
+            .unwrap(),
+    )
+}
+
+pub async fn delete(app: &Router, path: impl ToString, auth: Option<String>) -> Response {
+    Response(
+        app.clone()
+            .oneshot(request(path, Method::DELETE, None, auth))
+            .await
+            .unwrap(),
+    )
+}
+
+fn request(
+    path: impl ToString,
+    method: Method,
+    body: Option<Body>,
+    auth: Option<String>,
+) -> Request<Body> {
+    let mut request = Request::builder()
         .method(method)
         .uri(path.to_string())
         .header("Content-Type", "application/json");
+    if let Some(token) = auth {
+        request = request.header("Authorization", format!("Bearer {token}"));
+    }
 
     request.body(body.unwrap_or_else(Body::empty)).unwrap()
 }
@@ -364,6 +472,14 @@ impl Response {
         self.0.status()
     }
 
+    pub fn headers(&self) -> &axum::http::HeaderMap {
+        self.0.headers()
+    }
+
+    pub fn stream(self) -> axum::body::BodyDataStream {
+        self.0.into_body().into_data_stream()
+    }
+
     pub async fn body(self) -> Bytes {
         axum::body::to_bytes(self.0.into_body(), usize::MAX)
             .await
@@ -18,9 +18,9 @@ export async function fetchRepoInfos(
   let repos: Repo[];
 
   if (delegate) {
-    repos = await api.repo.getByDelegate(delegate, query);
+    repos = (await api.repo.getByDelegate(delegate, query)).items;
   } else {
-    repos = await api.repo.getAll(query);
+    repos = (await api.repo.getAll(query)).items;
   }
   const info = await Promise.all(
     repos
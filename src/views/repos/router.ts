@@ -347,7 +347,7 @@ async function loadPatchesView(
     params: {
       baseUrl: route.node,
       seedingPolicy,
-      patches,
+      patches: patches.items,
       status,
       repo,
       nodeAvatarUrl: node.avatarUrl,
@@ -377,7 +377,7 @@ async function loadIssuesView(
     params: {
       baseUrl: route.node,
       seedingPolicy,
-      issues,
+      issues: issues.items,
       status,
       repo,
       nodeAvatarUrl: node.avatarUrl,
@@ -623,7 +623,7 @@ async function loadHistoryView(
       peer: route.peer,
       revision: route.revision,
       tree,
-      commitHeaders,
+      commitHeaders: commitHeaders.items,
       nodeAvatarUrl: node.avatarUrl,
     },
   };